@@ -1,5 +1,5 @@
-use meet_in_the_middle::find_path;
-use state::{PositionInRectangle, RectangleSize};
+use meet_in_the_middle::{find_cheapest_path_with_heuristic, find_path_observed, find_path_packed, find_path_ranked, Discoverer, SearchOptions, StdoutObserver};
+use state::{ManhattanDistance, PositionInRectangle, RectangleSize};
 
 mod state;
 
@@ -12,7 +12,36 @@ fn main() {
 
     let target = PositionInRectangle::new(&rectangle_size, 2763, 1467);
 
-    let path: Vec<_> = find_path(&source, &target).into_iter().map(|n| n.to_string()).collect();
+    let solution = find_path_observed(&source, &target, &SearchOptions::default(), &mut StdoutObserver)
+        .expect("all positions in a rectangle are connected");
+    let stats = solution.stats().clone();
+
+    let path: Vec<_> = solution
+        .into_states()
+        .into_iter()
+        .map(|n| n.to_string())
+        .collect();
     println!("Path found with length: {:?}", path.len());
     println!("Start: {:?}, End: {:?}", path.iter().take(4).collect::<Vec<_>>(), path.iter().rev().take(4).rev().collect::<Vec<_>>());
-}
\ No newline at end of file
+    println!("Explored {} states, met after {:?} levels, took {:?}", stats.explored_count(), stats.meeting_levels, stats.elapsed);
+
+    let guided = find_cheapest_path_with_heuristic(&source, &target, &ManhattanDistance)
+        .expect("all positions in a rectangle are connected");
+    let guided_stats = guided.stats();
+    println!("With heuristic: path length {}, explored {} states, took {:?}", guided.len(), guided_stats.explored_count(), guided_stats.elapsed);
+
+    let packed = find_path_packed(&source, &target, &SearchOptions::default())
+        .expect("all positions in a rectangle are connected");
+    let packed_stats = packed.stats();
+    println!("Packed: path length {}, explored {} states, took {:?}", packed.len(), packed_stats.explored_count(), packed_stats.elapsed);
+
+    let ranked = find_path_ranked(&source, &target, &SearchOptions::default())
+        .expect("all positions in a rectangle are connected");
+    let ranked_stats = ranked.stats();
+    println!("Ranked: path length {}, explored {} states, took {:?}", ranked.len(), ranked_stats.explored_count(), ranked_stats.elapsed);
+
+    let small_rectangle = RectangleSize::new(5.try_into().unwrap(), 8.try_into().unwrap());
+    let mut discoverer = Discoverer::starting_at(&PositionInRectangle::new(&small_rectangle, 0, 0));
+    discoverer.by_ref().for_each(drop);
+    println!("Positions by distance from the corner of a 5x8 rectangle: {:?}", discoverer.states_per_depth());
+}
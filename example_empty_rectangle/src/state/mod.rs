@@ -1,5 +1,5 @@
 use std::{fmt::{Debug, Display}, num::NonZeroUsize};
-use meet_in_the_middle::State;
+use meet_in_the_middle::{Heuristic, IndexedState, PackedState, RankedState, SearchError, State, WeightedState};
 use transition::{Move, ALL_MOVES};
 
 mod transition;
@@ -57,6 +57,62 @@ impl<'a> State for PositionInRectangle<'a> {
     fn get_possible_transitions(&self) -> impl Iterator<Item = &Self::Transition> {
         PossibleTransitionIterator::new(self.clone())
     }
+
+    fn get_predecessors(&self) -> impl Iterator<Item = Result<(Self, Self::Transition), SearchError>> {
+        self.get_possible_transitions().map(|m| Ok((self.apply(m), m.inverse())))
+    }
+}
+
+/// Positions are numbered row by row, the size of the rectangle is taken from the prototype.
+impl<'a> PackedState for PositionInRectangle<'a> {
+    type Packed = u64;
+
+    fn pack(&self) -> u64 {
+        (self.y * self.size.width.get() + self.x) as u64
+    }
+
+    fn unpack(&self, packed: u64) -> Self {
+        let width = self.size.width.get();
+
+        PositionInRectangle {
+            size: self.size,
+            x: packed as usize % width,
+            y: packed as usize / width,
+        }
+    }
+}
+
+/// Indexes are the same numbers as the packed positions.
+impl<'a> IndexedState for PositionInRectangle<'a> {
+    fn index(&self) -> usize {
+        self.pack() as usize
+    }
+}
+
+impl<'a> RankedState for PositionInRectangle<'a> {
+    fn state_count(&self) -> usize {
+        self.size.width.get() * self.size.height.get()
+    }
+
+    fn unrank(&self, rank: usize) -> Self {
+        self.unpack(rank as u64)
+    }
+}
+
+/// Every move costs the same, this only allows using [`ManhattanDistance`].
+impl<'a> WeightedState for PositionInRectangle<'a> {
+    fn cost(&self, _transition: &Self::Transition) -> u64 {
+        1
+    }
+}
+
+/// Without obstacles this is exactly the number of moves needed.
+pub struct ManhattanDistance;
+
+impl<'a> Heuristic<PositionInRectangle<'a>> for ManhattanDistance {
+    fn estimate(&self, from: &PositionInRectangle<'a>, to: &PositionInRectangle<'a>) -> u64 {
+        (from.x.abs_diff(to.x) + from.y.abs_diff(to.y)) as u64
+    }
 }
 
 struct PossibleTransitionIterator<'a> {
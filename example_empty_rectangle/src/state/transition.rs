@@ -11,6 +11,16 @@ pub enum Move {
 pub static ALL_MOVES: [Move; 4] = [Move::Left, Move::Up, Move::Right, Move::Down];
 
 impl Move {
+    /// The move undoing this one.
+    pub fn inverse(&self) -> Move {
+        match self {
+            Move::Left => Move::Right,
+            Move::Up => Move::Down,
+            Move::Right => Move::Left,
+            Move::Down => Move::Up,
+        }
+    }
+
     pub fn allowed(&self, pos: &PositionInRectangle) -> bool {
         match &self {
             Move::Left => pos.x > 0,
@@ -1,19 +1,31 @@
-use std::array;
-
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use example_rubiks_cube::{cube_with_random_moves, solve_cube};
+use example_rubiks_cube::{cube_with_random_moves, get_solved_cube, solve_cube};
+use meet_in_the_middle::{find_path_with, PrecomputedGoal, SearchMode, SearchOptions, Strategy};
 use rand::{rngs, SeedableRng};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
-    static NUM_CUBES: usize = 10;
-    static MOVE_COUNT: u8 = 10;
+    bench_solving(c, 10, 10, 100);
+
+    // deeper scrambles used to get dramatically slower due to repeated searches per path segment,
+    // fewer samples keep the runtime of this group bearable
+    bench_solving(c, 3, 11, 10);
+
+    bench_strategies(c, 3, 8);
+
+    bench_precomputed_goal(c, 10, 8, 4);
 
+    #[cfg(feature = "parallel")]
+    bench_parallel(c, 3, 10);
+}
+
+fn bench_solving(c: &mut Criterion, num_cubes: usize, move_count: u8, sample_size: usize) {
     let mut rng = rngs::StdRng::seed_from_u64(1337);
-    let cubes: [_; NUM_CUBES] = array::from_fn(|i| {
-        (BenchmarkId::from_parameter(i), cube_with_random_moves(&mut rng, MOVE_COUNT))
+    let cubes = (0..num_cubes).map(|i| {
+        (BenchmarkId::from_parameter(i), cube_with_random_moves(&mut rng, move_count))
     });
 
-    let mut group = c.benchmark_group(format!("solve_{NUM_CUBES}_cubes_{MOVE_COUNT}_moves"));
+    let mut group = c.benchmark_group(format!("solve_{num_cubes}_cubes_{move_count}_moves"));
+    group.sample_size(sample_size);
 
     for (id, cube) in cubes {
         group.bench_with_input(id, &cube, |b, c| {
@@ -22,5 +34,75 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     }
 }
 
+/// Every cube has the same number of neighbors and predecessors, so the strategies only differ in
+/// how long each side goes on before the other one gets its turn.
+fn bench_strategies(c: &mut Criterion, num_cubes: usize, move_count: u8) {
+    let mut rng = rngs::StdRng::seed_from_u64(1337);
+    let cubes: Vec<_> = (0..num_cubes).map(|_| cube_with_random_moves(&mut rng, move_count)).collect();
+
+    let mut group = c.benchmark_group(format!("strategies_on_{num_cubes}_cubes_{move_count}_moves"));
+    group.sample_size(10);
+
+    for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+        for strategy in [Strategy::Alternate, Strategy::SmallerFrontier, Strategy::AlternateLevels] {
+            let options = SearchOptions { mode, strategy, ..Default::default() };
+
+            group.bench_with_input(BenchmarkId::new(format!("{mode:?}"), format!("{strategy:?}")), &options, |b, options| {
+                b.iter(|| {
+                    for cube in &cubes {
+                        find_path_with(cube, get_solved_cube(), options).unwrap();
+                    }
+                });
+            });
+        }
+    }
+}
+
+/// Solving many cubes with one [`PrecomputedGoal`], the time to build its table is not included.
+fn bench_precomputed_goal(c: &mut Criterion, num_cubes: usize, move_count: u8, goal_depth: usize) {
+    let mut rng = rngs::StdRng::seed_from_u64(1337);
+    let cubes: Vec<_> = (0..num_cubes).map(|_| cube_with_random_moves(&mut rng, move_count)).collect();
+    let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+    let goal = PrecomputedGoal::new(get_solved_cube(), goal_depth).unwrap();
+
+    let mut group = c.benchmark_group(format!("precomputed_goal_{num_cubes}_cubes_{move_count}_moves"));
+    group.sample_size(10);
+
+    group.bench_function("find_path_with", |b| b.iter(|| {
+        for cube in &cubes {
+            find_path_with(cube, get_solved_cube(), &options).unwrap();
+        }
+    }));
+
+    group.bench_function(format!("precomputed_depth_{goal_depth}"), |b| b.iter(|| {
+        for cube in &cubes {
+            goal.find_path(cube, &options).unwrap();
+        }
+    }));
+}
+
+/// Run with `--features parallel`, the speedup depends on the number of cores.
+#[cfg(feature = "parallel")]
+fn bench_parallel(c: &mut Criterion, num_cubes: usize, move_count: u8) {
+    let mut rng = rngs::StdRng::seed_from_u64(1337);
+    let cubes: Vec<_> = (0..num_cubes).map(|_| cube_with_random_moves(&mut rng, move_count)).collect();
+    let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+    let mut group = c.benchmark_group(format!("parallel_{num_cubes}_cubes_{move_count}_moves"));
+    group.sample_size(10);
+
+    group.bench_function("sequential", |b| b.iter(|| {
+        for cube in &cubes {
+            find_path_with(cube, get_solved_cube(), &options).unwrap();
+        }
+    }));
+
+    group.bench_function("parallel", |b| b.iter(|| {
+        for cube in &cubes {
+            meet_in_the_middle::find_path_parallel(cube, get_solved_cube(), &options).unwrap();
+        }
+    }));
+}
+
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
\ No newline at end of file
+criterion_main!(benches);
@@ -2,27 +2,28 @@ pub mod state;
 
 use std::{collections::HashSet, sync::OnceLock};
 
-use meet_in_the_middle::{find_path, State};
+use meet_in_the_middle::{find_path, find_transitions, Discoverer, SearchError, State};
 use rand::{seq::IteratorRandom, Rng};
 pub use state::Cube as RubiksCube;
 
-pub fn solve_cube(cube: &RubiksCube) -> impl IntoIterator<Item = RubiksCube> {
-    find_path(cube, &get_solved_cube())
+pub fn solve_cube(cube: &RubiksCube) -> Result<impl IntoIterator<Item = RubiksCube>, SearchError> {
+    find_path(cube, get_solved_cube())
 }
 
-pub fn solve_cube_with_transitions(cube: &RubiksCube) -> Vec<Step> {
-    let mut iterator = find_path(cube, &get_solved_cube()).into_iter();
-    let mut result = vec![];
+pub fn solve_cube_with_transitions(cube: &RubiksCube) -> Result<Vec<Step>, SearchError> {
+    let steps = find_transitions(cube, get_solved_cube())?
+        .steps()
+        .map(|(from_state, &transition)| Step { from_state: from_state.clone(), transition })
+        .collect();
 
-    let mut from = iterator.next().unwrap();
-
-    while let Some(to) = iterator.next() {
-        let transition = from.get_possible_transitions().find(|&t| from.apply(t) == to).unwrap().clone();
-        result.push(Step { from_state: from, transition });
-        from = to;
-    }
+    Ok(steps)
+}
 
-    result
+/// `distance_distribution(max_depth)[d]` is the number of cubes which can be solved with `d` moves, but not with fewer.
+pub fn distance_distribution(max_depth: usize) -> Vec<usize> {
+    let mut discoverer = Discoverer::starting_at(get_solved_cube()).with_max_depth(max_depth);
+    discoverer.by_ref().for_each(drop);
+    discoverer.states_per_depth().to_vec()
 }
 
 pub fn get_solved_cube() -> &'static RubiksCube {
@@ -54,8 +55,8 @@ pub fn cube_with_random_moves<TRng: Rng>(rng: &mut TRng, move_count: u8) -> Rubi
                     last_rotation.map(|lr| r.axis() != lr.axis() && r.line_index() != lr.line_index()).unwrap_or(true)
                 })
                 .choose(rng)
-                .unwrap()
-                .clone();
+                .copied()
+                .unwrap();
             
             let modified_cube = cube.apply(&transition);
 
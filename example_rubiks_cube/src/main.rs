@@ -1,4 +1,5 @@
-use example_rubiks_cube::{solve_cube_with_transitions, RubiksCube};
+use example_rubiks_cube::{get_solved_cube, RubiksCube};
+use meet_in_the_middle::{find_path_observed, SearchOptions, StdoutObserver};
 
 fn main() {
     // 0: white,
@@ -48,10 +49,13 @@ fn main() {
     //     [3, 5, 4, 3, 5, 5, 5, 5, 4]
     // ]).unwrap();
 
-    let solution: Vec<_> = solve_cube_with_transitions(&cube);
-    for step in solution {
-        println!("{}\n----------------------------------------------------------------", step.from_state);
-        println!("Apply {}", step.transition);
+    let solution = find_path_observed(&cube, get_solved_cube(), &SearchOptions::default(), &mut StdoutObserver)
+        .expect("cube should be solvable");
+
+    for (from_state, transition) in solution.steps() {
+        println!("{}\n----------------------------------------------------------------", from_state);
+        println!("Apply {}", transition);
     }
     println!("{}", RubiksCube::solved());
+    println!("{:#?}", solution.stats());
 }
@@ -4,7 +4,7 @@ use std::{error::Error, fmt::Display};
 pub struct Index(u8);
 
 impl Index {
-    pub fn to_shift(&self) -> u8 {
+    pub fn to_shift(self) -> u8 {
         self.0 * 3
     }
 }
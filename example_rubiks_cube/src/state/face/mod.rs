@@ -11,17 +11,21 @@ pub mod color;
 mod line;
 
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // 9 * 6 colors = 9 * 3 bits = 27 bits
 pub struct Face(u32);
 
+/// All indexes but the one of the center, which never moves
+const BORDER_INDEXES: [u8; 8] = [0, 1, 2, 3, 5, 6, 7, 8];
+
 impl Face {
     pub fn new(colors: [Color; 9]) -> Face {
         let mut value = 0u32;
         
-        for logical_index in 0..9usize {
+        for (logical_index, color) in colors.into_iter().enumerate() {
             let face_index = FaceIndex::try_from(logical_index as u8).unwrap();
             let shift = face_index.to_shift();
-            value |= (u32::from(colors[logical_index])) << shift;
+            value |= (u32::from(color)) << shift;
         }
 
         Face(value)
@@ -47,6 +51,19 @@ impl Face {
         self.0 = (self.0 & clear_stamp) | value_stamp;
     }
 
+    /// The colors around the center as a number in base 6, which fits into 21 bits.
+    pub fn pack_border(&self) -> u32 {
+        BORDER_INDEXES.iter().fold(0, |packed, &index| packed * 6 + u32::from(self.get(index.try_into().unwrap())))
+    }
+
+    /// Sets the colors around the center to the ones packed by [`Face::pack_border`].
+    pub fn unpack_border(&mut self, mut packed: u32) {
+        for &index in BORDER_INDEXES.iter().rev() {
+            self.set(index.try_into().unwrap(), ((packed % 6) as u8).try_into().unwrap());
+            packed /= 6;
+        }
+    }
+
     pub fn set_from_line(&mut self, source: &Face, line: &line::Id, mirror: bool) {
         let source_indices = line.indices();
         let target_indices = if mirror { line.mirrored().indices() } else { source_indices };
@@ -215,8 +232,7 @@ pub(crate) mod test {
 
     fn indexed_side() -> Face {
         let colors = array::from_fn(|i| ((i % 6) as u8).try_into().unwrap());
-        let side = Face::new(colors);
-        side
+        Face::new(colors)
     }
 
     pub(crate) fn random_face<TRand: rand::Rng>(rng: &mut TRand) -> Face {
@@ -4,7 +4,7 @@ use core::fmt::Write;
 use face::Color;
 use indenter::{indented, Format};
 
-use meet_in_the_middle::State;
+use meet_in_the_middle::{PackedState, SearchError, State, WeightedState};
 pub use face::{Face, LineId, LineIndex};
 use transition::{Axis, Times};
 
@@ -32,6 +32,7 @@ mod face;
 ///                   Y-Axis (through A and F)
 /// ``````
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cube {
     sides: [Face; 6]
 }
@@ -47,7 +48,7 @@ impl Cube {
         let mut color_counts = HashMap::new();
 
         for face_raw in faces_raw {
-            let colors_of_face_r: Result<Vec<Color>, _> = face_raw.map(|c| Color::try_from(c)).into_iter().collect();
+            let colors_of_face_r: Result<Vec<Color>, _> = face_raw.map(Color::try_from).into_iter().collect();
             let colors_of_face = colors_of_face_r?;
 
             let center_color = colors_of_face[4];
@@ -56,7 +57,7 @@ impl Cube {
             }
 
             for color in &colors_of_face {
-                let entry = color_counts.entry(color.clone());
+                let entry = color_counts.entry(*color);
                 match entry {
                     std::collections::hash_map::Entry::Vacant(_) => { entry.insert_entry(1); },
                     std::collections::hash_map::Entry::Occupied(_) => { entry.and_modify(|c| *c += 1); }
@@ -159,6 +160,42 @@ impl State for Cube {
     fn get_possible_transitions(&self) -> impl Iterator<Item = &Self::Transition> {
         transition::ALL_ROTATIONS.iter()
     }
+
+    fn get_predecessors(&self) -> impl Iterator<Item = Result<(Self, Self::Transition), SearchError>> {
+        transition::ALL_ROTATIONS.iter().map(|rotation| Ok((self.apply(&rotation.inverse()), *rotation)))
+    }
+}
+
+/// Each face takes 21 bits, see [`Face::pack_border`]. The centers are taken from the prototype, no move changes them.
+///
+/// Only searches which ask for it use this, i.e. [`meet_in_the_middle::find_path_packed`] and
+/// [`meet_in_the_middle::find_path_external`]. [`crate::solve_cube`] keeps whole cubes.
+impl PackedState for Cube {
+    type Packed = u128;
+
+    fn pack(&self) -> u128 {
+        self.sides.iter().fold(0, |packed, side| packed << 21 | u128::from(side.pack_border()))
+    }
+
+    fn unpack(&self, packed: u128) -> Cube {
+        let mut sides = self.sides.clone();
+
+        for (i, side) in sides.iter_mut().rev().enumerate() {
+            side.unpack_border((packed >> (21 * i)) as u32 & 0x1f_ffff);
+        }
+
+        Cube { sides }
+    }
+}
+
+/// Quarter turn metric: a half turn counts as two moves.
+impl WeightedState for Cube {
+    fn cost(&self, transition: &Self::Transition) -> u64 {
+        match transition.times() {
+            Times::Once | Times::Thrice => 1,
+            Times::Twice => 2,
+        }
+    }
 }
 
 impl Display for Cube {
@@ -168,7 +205,7 @@ impl Display for Cube {
         write!(indented(formatter).with_format(Format::Uniform { indentation }), "{}", self.sides[0])?;
 
         // without this, indenter somehow affects the next line, breaking the top borders of the second row of sides
-        writeln!(formatter, "")?;
+        writeln!(formatter)?;
 
         let mut lfrb_strings: [_; 4] = array::from_fn(|_| String::new());
 
@@ -214,6 +251,34 @@ mod tests {
         }        
     }
 
+    #[test]
+    fn predecessors_should_lead_back() {
+        let mut rnd = rngs::StdRng::from_seed([0; 32]);
+
+        for _ in 0..100 {
+            let cube = random_cube(&mut rnd);
+
+            for (predecessor, transition) in cube.get_predecessors().map(Result::unwrap) {
+                assert_eq!(predecessor.apply(&transition), cube, "transition: {transition}");
+            }
+        }
+    }
+
+    #[test]
+    fn unpacking_should_restore_neighbors() {
+        let mut rnd = rngs::StdRng::from_seed([0; 32]);
+
+        for _ in 0..100 {
+            let cube = random_cube(&mut rnd);
+
+            assert_eq!(cube.unpack(cube.pack()), cube);
+
+            for neighbor in cube.get_neighbors() {
+                assert_eq!(cube.unpack(neighbor.pack()), neighbor);
+            }
+        }
+    }
+
     fn random_cube<TRng: Rng>(rng: &mut TRng) -> Cube {
         let faces = array::from_fn(|_| random_face(rng));
         Cube::new(faces)
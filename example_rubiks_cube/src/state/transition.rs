@@ -3,6 +3,7 @@ use std::fmt::Display;
 use super::face::LineIndex as Index;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rotation(u8);
 
 pub static ALL_ROTATIONS: [Rotation; 18] = [
@@ -84,6 +85,17 @@ impl Rotation {
             _ => unreachable!(),
         }
     }
+
+    /// The rotation undoing this one.
+    pub fn inverse(&self) -> Rotation {
+        let times = match self.times() {
+            Times::Once => Times::Thrice,
+            Times::Twice => Times::Twice,
+            Times::Thrice => Times::Once,
+        };
+
+        Rotation::new(self.axis(), self.line_index(), times)
+    }
 }
 
 impl Display for Rotation {
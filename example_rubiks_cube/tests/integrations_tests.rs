@@ -1,8 +1,9 @@
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
-use example_rubiks_cube::{cube_with_random_moves, solve_cube, RubiksCube};
-use meet_in_the_middle::State;
+use example_rubiks_cube::{cube_with_random_moves, distance_distribution, solve_cube, solve_cube_with_transitions, RubiksCube};
+use example_rubiks_cube::state::{transition::{Axis, Rotation, Times}, LineIndex};
+use meet_in_the_middle::{all_shortest_paths, count_shortest_paths, distance, find_cheapest_path, find_path_packed, find_shortest_path, PrecomputedGoal, SearchOptions, Solution, State};
 
 #[test]
 fn can_solve_2_move() {
@@ -31,9 +32,177 @@ fn can_solve_7_moves() {
     }
 }
 
+#[test]
+fn transitions_lead_to_solved_cube() {
+    let mut rng = StdRng::from_seed([0; 32]);
+
+    for _ in 0..10 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 5);
+        let steps = solve_cube_with_transitions(&unsolved_cube).unwrap();
+
+        assert_eq!(steps[0].from_state, unsolved_cube);
+
+        let mut cube = unsolved_cube;
+        for step in steps {
+            assert_eq!(step.from_state, cube);
+            cube = cube.apply(&step.transition);
+        }
+
+        assert_eq!(cube, RubiksCube::solved());
+    }
+}
+
+#[test]
+fn shortest_solution_is_not_longer_than_scramble() {
+    let mut rng = StdRng::from_seed([0; 32]);
+
+    for _ in 0..10 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 5);
+        let solution = find_shortest_path(&unsolved_cube, &RubiksCube::solved()).unwrap();
+
+        assert!(solution.len() <= 5, "There should have been a solution with at most 5 moves for state\n{unsolved_cube}");
+    }
+}
+
+#[test]
+fn distance_matches_shortest_solution() {
+    let mut rng = StdRng::from_seed([0; 32]);
+
+    for _ in 0..10 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 5);
+        let solution = find_shortest_path(&unsolved_cube, &RubiksCube::solved()).unwrap();
+
+        assert_eq!(distance(&unsolved_cube, &RubiksCube::solved(), &SearchOptions::default()), Ok(Some(solution.len())));
+    }
+}
+
+#[test]
+fn packed_solution_is_shortest() {
+    let mut rng = StdRng::from_seed([0; 32]);
+
+    for _ in 0..10 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 5);
+        let expected = find_shortest_path(&unsolved_cube, &RubiksCube::solved()).unwrap();
+        let solution = find_path_packed(&unsolved_cube, &RubiksCube::solved(), &SearchOptions::default()).unwrap();
+
+        assert_solves_in(&solution, &unsolved_cube, expected.len());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn resumed_solution_is_shortest() {
+    use meet_in_the_middle::{find_path_checkpointed, resume_from, Checkpoint, SearchError};
+
+    let mut rng = StdRng::from_seed([0; 32]);
+    let unsolved_cube = cube_with_random_moves(&mut rng, 6);
+    let expected = find_shortest_path(&unsolved_cube, &RubiksCube::solved()).unwrap();
+
+    let path = std::env::temp_dir().join(format!("cube-{}.checkpoint", std::process::id()));
+    let checkpoint = Checkpoint { interval: std::time::Duration::ZERO, ..Checkpoint::new(&path) };
+    let interrupted = SearchOptions { max_depth: Some(1), ..Default::default() };
+    assert_eq!(find_path_checkpointed(&unsolved_cube, &RubiksCube::solved(), &interrupted, &checkpoint).map(|solution| solution.len()), Err(SearchError::DepthLimitReached));
+
+    let solution: Solution<RubiksCube, Rotation> = resume_from(&checkpoint, &SearchOptions::default()).unwrap();
+    assert_solves_in(&solution, &unsolved_cube, expected.len());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn precomputed_goal_finds_shortest_solutions() {
+    let mut rng = StdRng::from_seed([0; 32]);
+    let goal = PrecomputedGoal::new(&RubiksCube::solved(), 3).unwrap();
+
+    for _ in 0..10 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 6);
+        let expected = find_shortest_path(&unsolved_cube, &RubiksCube::solved()).unwrap();
+        let solution = goal.find_path(&unsolved_cube, &SearchOptions::default()).unwrap();
+
+        assert_solves_in(&solution, &unsolved_cube, expected.len());
+    }
+}
+
+#[test]
+fn deepening_finds_shortest_solutions() {
+    let mut rng = StdRng::from_seed([1; 32]);
+    let goal = PrecomputedGoal::new(&RubiksCube::solved(), 2).unwrap();
+
+    for _ in 0..3 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 5);
+        let expected = find_shortest_path(&unsolved_cube, &RubiksCube::solved()).unwrap();
+        let solution = goal.find_path_deepening(&unsolved_cube, &SearchOptions::default()).unwrap();
+
+        assert_solves_in(&solution, &unsolved_cube, expected.len());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn loaded_goal_matches_saved_goal() {
+    let goal = PrecomputedGoal::new(&RubiksCube::solved(), 2).unwrap();
+    let path = std::env::temp_dir().join(format!("cube-{}.goal", std::process::id()));
+    goal.save(&path).unwrap();
+
+    let loaded: PrecomputedGoal<RubiksCube, Rotation> = PrecomputedGoal::load(&path).unwrap();
+    assert_eq!(loaded.len(), goal.len());
+    assert_eq!(loaded.stats().states_per_level, vec![1, 18, 243]);
+
+    let unsolved_cube = cube_with_random_moves(&mut StdRng::from_seed([0; 32]), 4);
+    assert_eq!(loaded.find_path(&unsolved_cube, &SearchOptions::default()).unwrap().transitions(), goal.find_path(&unsolved_cube, &SearchOptions::default()).unwrap().transitions());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn distances_match_known_distribution() {
+    // number of positions by distance in the half turn metric, see https://www.cube20.org
+    assert_eq!(distance_distribution(3), vec![1, 18, 243, 3240]);
+}
+
+#[test]
+fn cheapest_solution_counts_half_turns_twice() {
+    let cube = RubiksCube::solved()
+        .apply(&Rotation::new(Axis::X, LineIndex::First, Times::Twice))
+        .apply(&Rotation::new(Axis::Y, LineIndex::First, Times::Once));
+
+    let solution = find_cheapest_path(&cube, &RubiksCube::solved()).unwrap();
+
+    assert_eq!(solution.cost(), 3);
+    assert_eq!(*solution.target(), RubiksCube::solved());
+}
+
+#[test]
+fn all_optimal_solutions_are_found() {
+    // opposite faces can be turned in either order
+    let cube = RubiksCube::solved()
+        .apply(&Rotation::new(Axis::X, LineIndex::First, Times::Once))
+        .apply(&Rotation::new(Axis::X, LineIndex::Last, Times::Once));
+
+    let solutions: Vec<_> = all_shortest_paths(&cube, &RubiksCube::solved(), &SearchOptions::default()).unwrap().collect();
+
+    assert_eq!(solutions.len(), 2);
+    assert!(solutions.iter().all(|solution| solution.len() == 2 && *solution.target() == RubiksCube::solved()));
+    assert_ne!(solutions[0], solutions[1]);
+}
+
+#[test]
+fn optimal_solutions_are_counted() {
+    let mut rng = StdRng::from_seed([0; 32]);
+
+    for _ in 0..5 {
+        let unsolved_cube = cube_with_random_moves(&mut rng, 4);
+        let (count, distance) = count_shortest_paths(&unsolved_cube, &RubiksCube::solved(), &SearchOptions::default()).unwrap();
+        let solutions = all_shortest_paths(&unsolved_cube, &RubiksCube::solved(), &SearchOptions::default()).unwrap();
+
+        assert_eq!(count, solutions.count() as u128);
+        assert!(distance <= 4);
+    }
+}
+
 fn can_solve_n_moves(rng: &mut StdRng, move_count: u8) {
     let unsolved_cube = cube_with_random_moves(rng, move_count);
-    let solution: Vec<_> = solve_cube(&unsolved_cube).into_iter().collect();
+    let solution: Vec<_> = solve_cube(&unsolved_cube).unwrap().into_iter().collect();
 
     let max_solution_nodes = move_count as usize + 1;
     assert!(solution.len() <= max_solution_nodes, "There should have been a solution with at most {max_solution_nodes} nodes for state\n{unsolved_cube}");
@@ -45,4 +214,13 @@ fn can_solve_n_moves(rng: &mut StdRng, move_count: u8) {
     while let Some([from, to]) = steps.next() {
         assert!(from.get_neighbors().any(|n| n == *to), "{from}\n{to}");
     }
-}
\ No newline at end of file
+}
+
+/// Asserts that `solution` leads from `unsolved_cube` to the solved cube with `move_count` rotations, each of them
+/// leading to the next cube.
+fn assert_solves_in(solution: &Solution<RubiksCube, Rotation>, unsolved_cube: &RubiksCube, move_count: usize) {
+    assert_eq!(solution.len(), move_count);
+    assert_eq!(solution.source(), unsolved_cube);
+    assert_eq!(solution.target(), &RubiksCube::solved());
+    assert!(solution.steps().zip(&solution.states()[1..]).all(|((from, rotation), to)| from.apply(rotation) == *to));
+}
@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use meet_in_the_middle::{find_path_with, SearchError, SearchMode, SearchOptions, State, Strategy};
+
+/// A number which can be incremented or multiplied by 2 to 10. Each number has ten successors,
+/// but only few predecessors, so searching from the target is much cheaper than from the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Number(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Increment,
+    Multiply(u64),
+}
+
+const OPERATIONS: [Operation; 10] = [
+    Operation::Increment,
+    Operation::Multiply(2),
+    Operation::Multiply(3),
+    Operation::Multiply(4),
+    Operation::Multiply(5),
+    Operation::Multiply(6),
+    Operation::Multiply(7),
+    Operation::Multiply(8),
+    Operation::Multiply(9),
+    Operation::Multiply(10),
+];
+
+impl State for Number {
+    type Transition = Operation;
+
+    fn apply(&self, change: &Operation) -> Number {
+        match change {
+            Operation::Increment => Number(self.0.saturating_add(1)),
+            Operation::Multiply(factor) => Number(self.0.saturating_mul(*factor)),
+        }
+    }
+
+    fn get_possible_transitions(&self) -> impl Iterator<Item = &Operation> {
+        OPERATIONS.iter()
+    }
+
+    fn get_predecessors(&self) -> impl Iterator<Item = Result<(Number, Operation), SearchError>> {
+        let number = self.0;
+
+        OPERATIONS.iter()
+            .filter_map(move |&operation| match operation {
+                Operation::Increment if number > 1 => Some(Ok((Number(number - 1), operation))),
+                Operation::Multiply(factor) if number >= factor && number.is_multiple_of(factor) => Some(Ok((Number(number / factor), operation))),
+                _ => None,
+            })
+    }
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("strategies_on_lopsided_graph");
+    group.sample_size(10);
+
+    for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+        for strategy in [Strategy::Alternate, Strategy::SmallerFrontier, Strategy::AlternateLevels] {
+            let options = SearchOptions { mode, strategy, ..Default::default() };
+
+            group.bench_with_input(BenchmarkId::new(format!("{mode:?}"), format!("{strategy:?}")), &options, |b, options| {
+                b.iter(|| find_path_with(&Number(1), &Number(987_654_321), options).unwrap());
+            });
+        }
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,260 @@
+use std::{slice, time::Instant};
+
+use crate::{path::Solution, solver::{join_at, Predecessor, Solver}, NoopObserver, SearchError, SearchOptions, SearchStats, Side, State};
+
+/// Finds every shortest way from `source` to `target`, the solutions are built one by one while iterating.
+///
+/// The search itself runs before this returns, each side keeping all predecessors found at the lowest
+/// level of a state, which needs more memory than [`crate::find_shortest_path`]. `options.mode` is ignored.
+pub fn all_shortest_paths<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<AllShortestPaths<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let started = Instant::now();
+    let mut solver: Solver<_, _> = Solver::new(slice::from_ref(source), slice::from_ref(target), options.clone());
+    solver.keep_all_predecessors();
+
+    let meetings = match solver.common_root() {
+        Some(root) => vec![root],
+        None => solver.run_layered(&mut NoopObserver)?,
+    };
+
+    let stats = solver.stats(&meetings[0], started);
+
+    Ok(AllShortestPaths {
+        solver,
+        meetings,
+        stats,
+        next: Some(Position { meeting: 0, source_choices: vec![], target_choices: vec![] }),
+    })
+}
+
+/// Counts the shortest ways from `source` to `target` and returns their number along with their length.
+///
+/// Each side counts the ways to every state it finds, which are combined where the sides meet. This is much
+/// faster than enumerating them with [`all_shortest_paths`]. Different transitions leading to the same state
+/// count as different ways, the count saturates at [`u128::MAX`]. `options.mode` is ignored.
+pub fn count_shortest_paths<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<(u128, usize), SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let mut solver: Solver<_, _> = Solver::new(slice::from_ref(source), slice::from_ref(target), options.clone());
+    solver.count_paths();
+
+    if solver.common_root().is_some() {
+        return Ok((1, 0));
+    }
+
+    let meetings = solver.run_layered(&mut NoopObserver)?;
+    let (source_level, target_level) = solver.levels_of(&meetings[0]);
+
+    let count = meetings.iter()
+        .map(|meeting| solver.path_count(Side::Source, meeting).saturating_mul(solver.path_count(Side::Target, meeting)))
+        .fold(0u128, u128::saturating_add);
+
+    Ok((count, source_level + target_level))
+}
+
+/// Iterator over all shortest solutions, see [`all_shortest_paths`].
+pub struct AllShortestPaths<TState, TTransition> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    solver: Solver<TState, TTransition>,
+
+    /// Each shortest path leads through exactly one of these, see [`Solver::run_layered`]
+    meetings: Vec<TState>,
+    stats: SearchStats,
+
+    /// `None` once all solutions were returned
+    next: Option<Position>,
+}
+
+/// Identifies a single solution: the meeting it leads through and which predecessor was picked at each
+/// step from there towards either end. Missing choices are zero, i.e. the first predecessor.
+struct Position {
+    meeting: usize,
+    source_choices: Vec<usize>,
+    target_choices: Vec<usize>,
+}
+
+impl<TState, TTransition> AllShortestPaths<TState, TTransition> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    /// The predecessors picked by `choices` on the way from `meeting` to the start of `side`,
+    /// `choices` is completed with zeros until the start is reached.
+    fn walk(&self, side: Side, meeting: &TState, choices: &mut Vec<usize>) -> Vec<&Predecessor<TState, TTransition>> {
+        let mut walked = vec![];
+        let mut current = meeting;
+
+        for step in 0.. {
+            let predecessors = self.solver.all_predecessors_of(side, current);
+            if predecessors.is_empty() {
+                break;
+            }
+
+            if step == choices.len() {
+                choices.push(0);
+            }
+
+            let predecessor = predecessors[choices[step]];
+            walked.push(predecessor);
+            current = &predecessor.state;
+        }
+
+        walked
+    }
+
+    /// Changes `choices` to the next way from `meeting` to the start of `side`, `false` if there is none.
+    fn advance(&self, side: Side, meeting: &TState, choices: &mut Vec<usize>) -> bool {
+        while let Some(choice) = choices.pop() {
+            let from = self.state_after(side, meeting, choices);
+
+            if choice + 1 < self.solver.all_predecessors_of(side, from).len() {
+                choices.push(choice + 1);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The state reached by following `choices` from `meeting` towards the start of `side`.
+    fn state_after<'a>(&'a self, side: Side, meeting: &'a TState, choices: &[usize]) -> &'a TState {
+        choices.iter().fold(meeting, |current, &choice| {
+            let predecessor = self.solver.all_predecessors_of(side, current)[choice];
+            &predecessor.state
+        })
+    }
+}
+
+impl<TState, TTransition> Iterator for AllShortestPaths<TState, TTransition> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    type Item = Solution<TState, TTransition>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut position = self.next.take()?;
+        let meeting = &self.meetings[position.meeting];
+
+        let from_source = self.walk(Side::Source, meeting, &mut position.source_choices);
+        let from_target = self.walk(Side::Target, meeting, &mut position.target_choices);
+        let solution = join_at(meeting, from_source.into_iter(), from_target.into_iter()).with_stats(self.stats.clone());
+
+        if self.advance(Side::Target, meeting, &mut position.target_choices) {
+            self.next = Some(position);
+        } else if self.advance(Side::Source, meeting, &mut position.source_choices) {
+            self.next = Some(Position { target_choices: vec![], ..position });
+        } else if position.meeting + 1 < self.meetings.len() {
+            self.next = Some(Position { meeting: position.meeting + 1, source_choices: vec![], target_choices: vec![] });
+        }
+
+        Some(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+    use crate::state::test::{assert_valid, for_random_graphs, random_node, GraphNode};
+
+    use super::*;
+
+    #[test]
+    fn should_find_all_ways_through_grid() {
+        let nodes = GraphNode::grid(3);
+
+        let solutions: Vec<_> = all_shortest_paths(&nodes[0], &nodes[8], &SearchOptions::default()).unwrap().collect();
+        let distinct: HashSet<_> = solutions.iter().map(|solution| solution.transitions().to_vec()).collect();
+
+        assert_eq!(solutions.len(), 6);
+        assert_eq!(distinct.len(), 6);
+        assert!(solutions.iter().all(|solution| solution.len() == 4 && solution.target() == &nodes[8]));
+    }
+
+    #[test]
+    fn should_allow_stopping_early() {
+        let nodes = GraphNode::grid(10);
+
+        let solutions: Vec<_> = all_shortest_paths(&nodes[0], &nodes[99], &SearchOptions::default()).unwrap().take(3).collect();
+
+        assert_eq!(solutions.len(), 3);
+    }
+
+    #[test]
+    fn should_handle_trivial_and_impossible_searches() {
+        let nodes = GraphNode::undirected(3, &[(0, 1)]);
+
+        assert_eq!(all_shortest_paths(&nodes[1], &nodes[1], &SearchOptions::default()).unwrap().count(), 1);
+        assert!(matches!(all_shortest_paths(&nodes[0], &nodes[2], &SearchOptions::default()), Err(SearchError::Unreachable)));
+    }
+
+    #[test]
+    fn should_count_ways_through_grid() {
+        let nodes = GraphNode::grid(10);
+
+        assert_eq!(count_shortest_paths(&nodes[0], &nodes[99], &SearchOptions::default()), Ok((48620, 18)));
+        assert_eq!(count_shortest_paths(&nodes[5], &nodes[5], &SearchOptions::default()), Ok((1, 0)));
+    }
+
+    #[test]
+    fn all_paths_should_match_breadth_first_search() {
+        for_random_graphs(1000, 30, 3, |nodes, rng| {
+            let source = random_node(rng, nodes);
+            let target = random_node(rng, nodes);
+
+            let expected = breadth_first_path_count(source, target);
+
+            match all_shortest_paths(source, target, &SearchOptions::default()) {
+                Ok(solutions) => {
+                    let solutions: Vec<_> = solutions.collect();
+                    let (expected_count, expected_length) = expected.expect("target is reachable");
+
+                    assert_eq!(solutions.len() as u128, expected_count, "from {source:?} to {target:?}");
+                    assert_eq!(count_shortest_paths(source, target, &SearchOptions::default()), Ok((expected_count, expected_length)));
+
+                    for solution in solutions {
+                        assert_eq!(solution.len(), expected_length);
+                        assert_valid(&solution, source, target);
+                    }
+                },
+                Err(error) => {
+                    assert_eq!(error, SearchError::Unreachable);
+                    assert_eq!(expected, None, "from {source:?} to {target:?}");
+                    assert_eq!(count_shortest_paths(source, target, &SearchOptions::default()), Err(SearchError::Unreachable));
+                },
+            }
+        });
+    }
+
+    /// Number of shortest paths and their length by plain unidirectional breadth first search,
+    /// transitions leading to the same state count as different paths.
+    fn breadth_first_path_count<TState: State>(source: &TState, target: &TState) -> Option<(u128, usize)> {
+        let mut reached = HashMap::from([(source.clone(), (1u128, 0))]);
+        let mut queue = VecDeque::from([source.clone()]);
+
+        while let Some(state) = queue.pop_front() {
+            let (count, distance) = reached[&state];
+
+            if state == *target {
+                return Some((count, distance));
+            }
+
+            for neighbor in state.get_neighbors() {
+                match reached.entry(neighbor.clone()) {
+                    Entry::Vacant(entry) => {
+                        entry.insert((count, distance + 1));
+                        queue.push_back(neighbor);
+                    },
+                    Entry::Occupied(mut entry) if entry.get().1 == distance + 1 => entry.get_mut().0 += count,
+                    Entry::Occupied(_) => {},
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,244 @@
+use std::{ffi::OsString, fs::{self, File}, io::{self, BufReader, BufWriter, ErrorKind, Read, Write}, path::{Path, PathBuf}, slice, time::{Duration, Instant}};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{path::Solution, solver::Solver, NoopObserver, SearchError, SearchOptions, State};
+
+/// Where [`find_path_checkpointed`] saves its progress and how often.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// The file is replaced by each new checkpoint, never left half written
+    pub path: PathBuf,
+
+    /// Minimum time between two checkpoints, they are only saved once a level is complete
+    pub interval: Duration,
+}
+
+impl Checkpoint {
+    /// Saves to `path` at most every ten minutes.
+    pub fn new(path: impl Into<PathBuf>) -> Checkpoint {
+        Checkpoint { path: path.into(), interval: Duration::from_secs(600) }
+    }
+}
+
+/// Like [`crate::find_path_with`], saving both sides of the search to `checkpoint.path` from time to time.
+///
+/// Checkpoints are only written between levels, so the search expands whole levels whatever `options.mode`
+/// says and the path is as short as possible. If the search is interrupted, [`resume_from`] continues from
+/// the last checkpoint. Fails with [`SearchError::Io`] if a checkpoint cannot be written.
+pub fn find_path_checkpointed<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions, checkpoint: &Checkpoint) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition> + Serialize + DeserializeOwned,
+    TTransition : Clone + Serialize + DeserializeOwned,
+{
+    let started = Instant::now();
+    let solver = Solver::new(slice::from_ref(source), slice::from_ref(target), options.clone());
+
+    if let Some(root) = solver.common_root() {
+        let stats = solver.stats(&root, started);
+        return Ok(solver.solution_through(&root).with_stats(stats));
+    }
+
+    continue_search(solver, false, checkpoint, started)
+}
+
+/// Continues the search saved in `checkpoint.path` by [`find_path_checkpointed`] and keeps saving checkpoints there.
+///
+/// The source and target are taken from the checkpoint, `options` may differ from those of the interrupted
+/// search, e.g. to allow more time. The stats carry on from those saved in the checkpoint, except for
+/// [`crate::SearchStats::elapsed`] which only covers the time since resuming. Fails with [`SearchError::Io`]
+/// if the checkpoint cannot be read, with [`ErrorKind::InvalidData`] if it is no checkpoint of this version
+/// or of different types of states.
+pub fn resume_from<TState, TTransition>(checkpoint: &Checkpoint, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition> + Serialize + DeserializeOwned,
+    TTransition : Clone + Serialize + DeserializeOwned,
+{
+    let started = Instant::now();
+    let saved = load(&checkpoint.path)?;
+    let (solver, expanded_source) = Solver::from_saved(saved, options.clone());
+
+    continue_search(solver, expanded_source, checkpoint, started)
+}
+
+fn continue_search<TState, TTransition>(mut solver: Solver<TState, TTransition>, expanded_source: bool, checkpoint: &Checkpoint, started: Instant) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition> + Serialize + DeserializeOwned,
+    TTransition : Clone + Serialize + DeserializeOwned,
+{
+    let mut last_saved = Instant::now();
+
+    let node_on_path = solver.run_layered_from(&mut NoopObserver, expanded_source, |solver, expanded_source| {
+        if last_saved.elapsed() >= checkpoint.interval {
+            save(&solver.to_saved(expanded_source), &checkpoint.path)?;
+            last_saved = Instant::now();
+        }
+
+        Ok(())
+    })?.swap_remove(0);
+
+    let stats = solver.stats(&node_on_path, started);
+    Ok(solver.solution_through(&node_on_path).with_stats(stats))
+}
+
+/// Starts every checkpoint file, followed by [`VERSION`].
+const MAGIC: &[u8; 8] = b"MITMCKPT";
+
+/// Changes whenever [`SavedSearch`] changes, checkpoints of other versions are rejected.
+const VERSION: u32 = 1;
+
+/// Both sides of a search between two levels.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedSearch<TState, TTransition> {
+    pub(crate) from_source: SavedDiscoverer<TState, TTransition>,
+    pub(crate) from_target: SavedDiscoverer<TState, TTransition>,
+    pub(crate) expanded_source: bool,
+}
+
+/// One side of a search between two levels.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedDiscoverer<TState, TTransition> {
+    /// In the order they were seen, the roots come first
+    pub(crate) states: Vec<SavedState<TState, TTransition>>,
+
+    /// Positions in `states` of the states to expand next
+    pub(crate) frontier: Vec<usize>,
+
+    pub(crate) current_level: usize,
+    pub(crate) states_per_level: Vec<usize>,
+    pub(crate) peak_frontier_size: usize,
+}
+
+impl<TState, TTransition> SavedDiscoverer<TState, TTransition> {
+    /// Whether all positions refer to saved states, so a damaged checkpoint is rejected instead of causing a panic.
+    fn is_consistent(&self) -> bool {
+        let len = self.states.len();
+
+        self.frontier.iter().all(|&position| position < len)
+            && self.states.iter().all(|saved_state| saved_state.predecessor.as_ref().is_none_or(|(position, _)| *position < len))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedState<TState, TTransition> {
+    pub(crate) state: TState,
+    pub(crate) level: usize,
+
+    /// Position of the predecessor in [`SavedDiscoverer::states`], `None` for roots
+    pub(crate) predecessor: Option<(usize, TTransition)>,
+}
+
+fn save<TState, TTransition>(saved: &SavedSearch<TState, TTransition>, path: &Path) -> io::Result<()> where
+    TState : Serialize,
+    TTransition : Serialize,
+{
+    save_versioned(saved, MAGIC, VERSION, path)
+}
+
+fn load<TState, TTransition>(path: &Path) -> io::Result<SavedSearch<TState, TTransition>> where
+    TState : DeserializeOwned,
+    TTransition : DeserializeOwned,
+{
+    let saved: SavedSearch<TState, TTransition> = load_versioned(MAGIC, VERSION, path)?;
+
+    if !saved.from_source.is_consistent() || !saved.from_target.is_consistent() {
+        return Err(io::Error::new(ErrorKind::InvalidData, "damaged checkpoint"));
+    }
+
+    Ok(saved)
+}
+
+/// Writes `magic`, `version` and `value` next to `path` first, then replaces `path` with it, so it is never left half written.
+pub(crate) fn save_versioned<T: Serialize>(value: &T, magic: &[u8; 8], version: u32, path: &Path) -> io::Result<()> {
+    let mut partial_path = OsString::from(path);
+    partial_path.push(".partial");
+
+    let mut writer = BufWriter::new(File::create(&partial_path)?);
+    writer.write_all(magic)?;
+    writer.write_all(&version.to_le_bytes())?;
+    bincode::serialize_into(&mut writer, value).map_err(|error| into_io_error(*error))?;
+    writer.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+
+    fs::rename(&partial_path, path)
+}
+
+/// Reads what [`save_versioned`] wrote, fails with [`ErrorKind::InvalidData`] if `magic` or `version` differ.
+pub(crate) fn load_versioned<T: DeserializeOwned>(magic: &[u8; 8], version: u32, path: &Path) -> io::Result<T> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut header = [0; 12];
+    reader.read_exact(&mut header)?;
+
+    if header[..8] != *magic {
+        return Err(io::Error::new(ErrorKind::InvalidData, "unexpected kind of file"));
+    }
+
+    if header[8..] != version.to_le_bytes() {
+        return Err(io::Error::new(ErrorKind::InvalidData, "file of a different version"));
+    }
+
+    bincode::deserialize_from(reader).map_err(|error| into_io_error(*error))
+}
+
+fn into_io_error(error: bincode::ErrorKind) -> io::Error {
+    match error {
+        bincode::ErrorKind::Io(error) => error,
+        error => io::Error::new(ErrorKind::InvalidData, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{find_shortest_path, state::test::assert_valid};
+
+    use super::*;
+
+    /// A square on a chess board, knights move from one to the next.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    struct Square(i8, i8);
+
+    const KNIGHT_MOVES: [(i8, i8); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+
+    impl State for Square {
+        type Transition = (i8, i8);
+
+        fn apply(&self, change: &(i8, i8)) -> Square {
+            Square(self.0 + change.0, self.1 + change.1)
+        }
+
+        fn get_possible_transitions(&self) -> impl Iterator<Item = &(i8, i8)> {
+            KNIGHT_MOVES.iter().filter(|(x, y)| (0..8).contains(&(self.0 + x)) && (0..8).contains(&(self.1 + y)))
+        }
+    }
+
+    fn checkpoint(name: &str) -> Checkpoint {
+        let path = std::env::temp_dir().join(format!("meet_in_the_middle-{name}-{}.checkpoint", std::process::id()));
+        Checkpoint { path, interval: Duration::ZERO }
+    }
+
+    #[test]
+    fn resumed_search_should_find_shortest_path() {
+        let checkpoint = checkpoint("resume");
+        let expected = find_shortest_path(&Square(0, 0), &Square(7, 7)).unwrap();
+
+        let interrupted = SearchOptions { max_depth: Some(2), ..Default::default() };
+        assert_eq!(find_path_checkpointed(&Square(0, 0), &Square(7, 7), &interrupted, &checkpoint), Err(SearchError::DepthLimitReached));
+
+        let solution: Solution<Square, _> = resume_from(&checkpoint, &SearchOptions::default()).unwrap();
+        assert_eq!(solution.len(), expected.len());
+        assert_valid(&solution, &Square(0, 0), &Square(7, 7));
+        assert_eq!(solution.stats().meeting_levels.0 + solution.stats().meeting_levels.1, expected.len());
+
+        fs::remove_file(&checkpoint.path).unwrap();
+    }
+
+    #[test]
+    fn should_reject_invalid_checkpoints() {
+        let checkpoint = checkpoint("invalid");
+
+        fs::write(&checkpoint.path, b"MITMCKPT\x02\0\0\0").unwrap();
+        assert_eq!(resume_from::<Square, _>(&checkpoint, &SearchOptions::default()).map(|solution| solution.len()), Err(SearchError::Io(ErrorKind::InvalidData)));
+
+        fs::write(&checkpoint.path, b"something else").unwrap();
+        assert_eq!(resume_from::<Square, _>(&checkpoint, &SearchOptions::default()).map(|solution| solution.len()), Err(SearchError::Io(ErrorKind::InvalidData)));
+
+        fs::remove_file(&checkpoint.path).unwrap();
+        assert_eq!(resume_from::<Square, _>(&checkpoint, &SearchOptions::default()).map(|solution| solution.len()), Err(SearchError::Io(ErrorKind::NotFound)));
+    }
+}
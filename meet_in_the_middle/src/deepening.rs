@@ -0,0 +1,219 @@
+use std::time::Instant;
+
+use crate::{heuristic::NoHeuristic, options::Limits, path::Solution, solver::neighbors, Heuristic, PrecomputedGoal, SearchError, SearchOptions, SearchStats, Side, SideStats, State};
+
+impl<TState, TTransition> PrecomputedGoal<TState, TTransition> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    /// Like [`PrecomputedGoal::find_path`], but only the states on the current way from `source` are kept besides the table.
+    ///
+    /// Depth first searches from `source` are repeated with a growing bound on the length of the whole way. States
+    /// in the table are known to be exactly that far from the target, all others at least one transition further
+    /// than its deepest level. States are visited again in every round and on every way leading to them, so this
+    /// trades time for memory. `options.max_depth` limits the transitions from `source`, `options.max_states` the
+    /// states in the table plus those on the current way, `options.mode` and `options.strategy` are ignored.
+    ///
+    /// No set of seen states is kept, so the stats of the source side are left empty instead of counting the
+    /// same states again and again, only [`SearchStats::meeting_levels`] tells how far the source is from the table.
+    pub fn find_path_deepening(&self, source: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> {
+        self.find_path_deepening_with_heuristic(source, &NoHeuristic, options)
+    }
+
+    /// Like [`PrecomputedGoal::find_path_deepening`], states which `heuristic` estimates to be too far from the target are not expanded, i.e. IDA*.
+    ///
+    /// The estimate is taken as a number of transitions, the path is only guaranteed to be shortest if it is admissible.
+    pub fn find_path_deepening_with_heuristic<THeuristic>(&self, source: &TState, heuristic: &THeuristic, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+        THeuristic : Heuristic<TState>,
+    {
+        let started = Instant::now();
+        let mut search = DeepeningSearch {
+            goal: self,
+            heuristic,
+            limits: Limits::new(options),
+            max_depth: options.max_depth.unwrap_or(usize::MAX),
+            way: vec![source.clone()],
+            transitions: vec![],
+            next_bound: None,
+            depth_limited: false,
+        };
+
+        let mut bound = search.lower_bound(source).ok_or(SearchError::Unreachable)?;
+
+        let table_level = loop {
+            search.depth_limited = false;
+
+            if let Some(table_level) = search.visit(bound)? {
+                break table_level;
+            }
+
+            bound = match (search.next_bound.take(), search.depth_limited) {
+                (Some(next_bound), _) => next_bound,
+                (None, true) => return Err(SearchError::DepthLimitReached),
+                (None, false) => return Err(SearchError::Unreachable),
+            };
+        };
+
+        let stats = SearchStats {
+            from_source: SideStats::default(),
+            from_target: self.stats(),
+            meeting_levels: (search.transitions.len(), table_level),
+            elapsed: started.elapsed(),
+        };
+
+        let (mut states, mut transitions) = (search.way, search.transitions);
+        self.extend_to_target(&mut states, &mut transitions);
+
+        Ok(Solution::new(states, transitions).with_stats(stats))
+    }
+}
+
+/// One depth first search after another from the source of [`PrecomputedGoal::find_path_deepening`].
+struct DeepeningSearch<'a, TState, TTransition, THeuristic> {
+    goal: &'a PrecomputedGoal<TState, TTransition>,
+    heuristic: &'a THeuristic,
+    limits: Limits<'a>,
+    max_depth: usize,
+
+    /// From the source to the state being visited
+    way: Vec<TState>,
+    transitions: Vec<TTransition>,
+
+    /// The lowest bound beyond the current one which would let the next round visit more states
+    next_bound: Option<usize>,
+
+    /// Whether states were left out because of the maximum depth in the current round
+    depth_limited: bool,
+}
+
+impl<TState, TTransition, THeuristic> DeepeningSearch<'_, TState, TTransition, THeuristic> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+    THeuristic : Heuristic<TState>,
+{
+    /// Fewest transitions `state` could be away from the target, `None` if it cannot reach the target at all.
+    fn lower_bound(&self, state: &TState) -> Option<usize> {
+        match self.goal.distance(state) {
+            Some(table_level) => Some(table_level),
+            None if self.goal.is_complete() => None,
+            None => {
+                let estimate = usize::try_from(self.heuristic.estimate(state, self.goal.target())).unwrap_or(usize::MAX);
+                Some(estimate.max(self.goal.depth() + 1))
+            },
+        }
+    }
+
+    /// Searches on from the last state of the way as long as the way to the target can be at most `bound` long.
+    ///
+    /// Returns the level in the table of the last state once the way reached the table, the way is left as it was otherwise.
+    fn visit(&mut self, bound: usize) -> Result<Option<usize>, SearchError> {
+        let depth = self.transitions.len();
+        let state = self.way.last().expect("the way starts at the source").clone();
+        self.limits.check(self.goal.len() + self.way.len())?;
+
+        let Some(lower_bound) = self.lower_bound(&state) else {
+            return Ok(None);
+        };
+
+        if depth.saturating_add(lower_bound) > bound {
+            let exceeding = depth.saturating_add(lower_bound);
+            self.next_bound = Some(self.next_bound.map_or(exceeding, |next_bound| next_bound.min(exceeding)));
+            return Ok(None);
+        }
+
+        if let Some(table_level) = self.goal.distance(&state) {
+            return Ok(Some(table_level));
+        }
+
+        if depth >= self.max_depth {
+            self.depth_limited = true;
+            return Ok(None);
+        }
+
+        for (neighbor, transition) in neighbors(&state, Side::Source)? {
+            if self.way.contains(&neighbor) {
+                continue;
+            }
+
+            self.way.push(neighbor);
+            self.transitions.push(transition);
+
+            if let Some(table_level) = self.visit(bound)? {
+                return Ok(Some(table_level));
+            }
+
+            self.way.pop();
+            self.transitions.pop();
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use rand::{rngs, Rng, SeedableRng};
+
+    use crate::state::test::{assert_matches_shortest_path, GraphNode, GridDistance};
+
+    use super::*;
+
+    /// Counts the estimates of `heuristic`, one for each visit of a state outside the table.
+    struct Counting<THeuristic> {
+        heuristic: THeuristic,
+        estimates: Cell<usize>,
+    }
+
+    impl<THeuristic> Counting<THeuristic> {
+        fn new(heuristic: THeuristic) -> Counting<THeuristic> {
+            Counting { heuristic, estimates: Cell::new(0) }
+        }
+    }
+
+    impl<THeuristic: Heuristic<GraphNode>> Heuristic<GraphNode> for Counting<THeuristic> {
+        fn estimate(&self, from: &GraphNode, to: &GraphNode) -> u64 {
+            self.estimates.set(self.estimates.get() + 1);
+            self.heuristic.estimate(from, to)
+        }
+    }
+
+    #[test]
+    fn should_find_shortest_path() {
+        let nodes = GraphNode::grid(6);
+        let goal = PrecomputedGoal::new(&nodes[35], 2).unwrap();
+
+        let solution = goal.find_path_deepening(&nodes[0], &SearchOptions::default()).unwrap();
+        assert_eq!(solution.len(), 10);
+        assert_eq!(solution.target(), &nodes[35]);
+        assert_eq!(solution.stats().meeting_levels, (8, 2));
+
+        let shallow = SearchOptions { max_depth: Some(7), ..Default::default() };
+        assert_eq!(goal.find_path_deepening(&nodes[0], &shallow).map(|solution| solution.len()), Err(SearchError::DepthLimitReached));
+    }
+
+    #[test]
+    fn heuristic_should_visit_fewer_states() {
+        let nodes = GraphNode::grid(6);
+        let goal = PrecomputedGoal::new(&nodes[35], 2).unwrap();
+
+        let (blind, manhattan) = (Counting::new(NoHeuristic), Counting::new(GridDistance(6)));
+
+        let unguided = goal.find_path_deepening_with_heuristic(&nodes[0], &blind, &SearchOptions::default()).unwrap();
+        let guided = goal.find_path_deepening_with_heuristic(&nodes[0], &manhattan, &SearchOptions::default()).unwrap();
+
+        assert_eq!(guided.len(), unguided.len());
+        assert_eq!(guided.stats().from_source, SideStats::default());
+        assert!(manhattan.estimates.get() < blind.estimates.get());
+    }
+
+    #[test]
+    fn deepening_path_should_match_shortest_path() {
+        let mut rng = rngs::StdRng::seed_from_u64(7);
+
+        assert_matches_shortest_path(1000, |source, target| {
+            PrecomputedGoal::new(target, rng.random_range(0..4))?.find_path_deepening(source, &SearchOptions::default())
+        });
+    }
+}
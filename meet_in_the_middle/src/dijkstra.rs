@@ -0,0 +1,584 @@
+use std::{cmp::Ordering, collections::{hash_map::Entry, BinaryHeap, HashMap}, time::Instant};
+
+use crate::{heuristic::NoHeuristic, options::Limits, path::Solution, solver::{join_at, neighbors, Predecessor}, Heuristic, NoopObserver, SearchError, SearchObserver, SearchOptions, SearchStats, Side, SideStats, WeightedState};
+
+/// Finds the way from `source` to `target` with the lowest total cost, see [`Solution::cost`].
+///
+/// Ways costing more than `u64::MAX` in total are ignored, as if their last transition did not exist.
+pub fn find_cheapest_path<TState, TTransition>(source: &TState, target: &TState) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    find_cheapest_path_observed(source, target, &SearchOptions::default(), &mut NoopObserver)
+}
+
+/// Like [`find_cheapest_path`], within the limits given by `options` and notifying `observer` about the progress of the search.
+///
+/// `options.mode` is ignored. States reached over more than `options.max_depth` transitions
+/// are not expanded any further, the result is only guaranteed to be the cheapest without a depth limit.
+/// The stats of the solution count the states whose cost from either side was final.
+pub fn find_cheapest_path_observed<TState, TTransition, TObserver>(source: &TState, target: &TState, options: &SearchOptions, observer: &mut TObserver) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+    TObserver : SearchObserver<TState>,
+{
+    search(source, target, None::<&NoHeuristic>, options, observer)
+}
+
+/// Like [`find_cheapest_path`], but states which `heuristic` estimates to be closer to the other side are settled first.
+///
+/// With a good estimate far fewer states are explored. The path is still the cheapest as long as
+/// the estimate never exceeds the actual cost.
+pub fn find_cheapest_path_with_heuristic<TState, TTransition, THeuristic>(source: &TState, target: &TState, heuristic: &THeuristic) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+    THeuristic : Heuristic<TState>,
+{
+    find_cheapest_path_with_heuristic_observed(source, target, heuristic, &SearchOptions::default(), &mut NoopObserver)
+}
+
+/// Like [`find_cheapest_path_with_heuristic`], within the limits given by `options` and notifying `observer`,
+/// see [`find_cheapest_path_observed`].
+pub fn find_cheapest_path_with_heuristic_observed<TState, TTransition, THeuristic, TObserver>(source: &TState, target: &TState, heuristic: &THeuristic, options: &SearchOptions, observer: &mut TObserver) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+    THeuristic : Heuristic<TState>,
+    TObserver : SearchObserver<TState>,
+{
+    search(source, target, Some(heuristic), options, observer)
+}
+
+fn search<TState, TTransition, THeuristic, TObserver>(source: &TState, target: &TState, heuristic: Option<&THeuristic>, options: &SearchOptions, observer: &mut TObserver) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+    THeuristic : Heuristic<TState>,
+    TObserver : SearchObserver<TState>,
+{
+    let started = Instant::now();
+    let mut solver = CostSolver::new(source.clone(), target.clone(), heuristic, options.clone());
+
+    let result = solver.run(observer).map(|node_on_path| {
+        let stats = solver.stats(&node_on_path, started);
+        solver.solution_through(&node_on_path).with_stats(stats)
+    });
+
+    observer.search_finished(result.as_ref().map(Solution::len).map_err(|error| *error));
+    result
+}
+
+/// Bidirectional Dijkstra, each side settles the state with the lowest priority first. Without a
+/// heuristic the priority is the cost from where the side started, with one it is front-to-end A*
+/// where the priority additionally contains the estimated cost between the state and where the other side started.
+struct CostSolver<'h, TState, TTransition, THeuristic> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+    THeuristic : Heuristic<TState>,
+{
+    from_source: CostDiscoverer<TState, TTransition>,
+    from_target: CostDiscoverer<TState, TTransition>,
+    heuristic: Option<&'h THeuristic>,
+    options: SearchOptions,
+}
+
+impl<'h, TState, TTransition, THeuristic> CostSolver<'h, TState, TTransition, THeuristic> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+    THeuristic : Heuristic<TState>,
+{
+    fn new(source: TState, target: TState, heuristic: Option<&'h THeuristic>, options: SearchOptions) -> CostSolver<'h, TState, TTransition, THeuristic> {
+        let max_level = options.max_depth.unwrap_or(usize::MAX);
+        let estimate = heuristic.map_or(0, |heuristic| heuristic.estimate(&source, &target));
+
+        CostSolver {
+            from_source: CostDiscoverer::new(source.clone(), estimate, Side::Source, max_level),
+            from_target: CostDiscoverer::new(target.clone(), estimate, Side::Target, max_level),
+            heuristic,
+            options,
+        }
+    }
+
+    /// Settles states from the side with the lower priority until no cheaper path than the best one seen so far can exist.
+    ///
+    /// Without a heuristic, every state still waiting on the source side costs at least `source_min` to
+    /// reach, every state waiting on the target side at least `target_min`. A path not yet seen has to
+    /// leave the settled states of both sides, so it costs at least `source_min + target_min`.
+    ///
+    /// With an admissible heuristic, a cheaper path than the best known one would contain a waiting
+    /// state on each side whose priority is below the cost of the best known path. So we can stop as
+    /// soon as either side has no such state left.
+    fn run(&mut self, observer: &mut impl SearchObserver<TState>) -> Result<TState, SearchError> {
+        let mut limits = Limits::new(&self.options);
+        let mut best: Option<(u64, TState)> = None;
+
+        if let Some(cost) = self.from_target.cost_of(self.from_source.root()) {
+            best = Some((cost, self.from_source.root().clone()));
+        }
+
+        // once either side has settled everything it can reach, no further meetings are possible
+        while let (Some(source_min), Some(target_min)) = (self.from_source.min_priority(), self.from_target.min_priority()) {
+            let lower_bound = match self.heuristic {
+                None => source_min.saturating_add(target_min),
+                Some(_) => source_min.max(target_min),
+            };
+
+            if best.as_ref().is_some_and(|(best_cost, _)| lower_bound >= *best_cost) {
+                break;
+            }
+
+            let (discoverer, other_discoverer) = if source_min <= target_min {
+                (&mut self.from_source, &self.from_target)
+            } else {
+                (&mut self.from_target, &self.from_source)
+            };
+
+            let other_root = other_discoverer.root();
+            let side = discoverer.side;
+            let estimate = |state: &TState| match (self.heuristic, side) {
+                (None, _) => 0,
+                (Some(heuristic), Side::Source) => heuristic.estimate(state, other_root),
+                (Some(heuristic), Side::Target) => heuristic.estimate(other_root, state),
+            };
+
+            for state in discoverer.settle_next(estimate, observer)? {
+                let Some(other_cost) = other_discoverer.cost_of(&state) else {
+                    continue;
+                };
+
+                let Some(cost) = discoverer.cost_of(&state).expect("was just reached").checked_add(other_cost) else {
+                    continue;
+                };
+
+                if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                    best = Some((cost, state));
+                }
+            }
+
+            limits.check(self.from_source.explored_count() + self.from_target.explored_count())?;
+        }
+
+        match best {
+            Some((_, node_on_path)) => {
+                observer.meeting_found(&node_on_path);
+                Ok(node_on_path)
+            },
+            None if self.from_source.depth_limited || self.from_target.depth_limited => Err(SearchError::DepthLimitReached),
+            None => Err(SearchError::Unreachable),
+        }
+    }
+
+    fn stats(&self, node_on_path: &TState, started: Instant) -> SearchStats {
+        let source_level = self.from_source.level_of(node_on_path).expect("node_on_path was reached by both sides");
+        let target_level = self.from_target.level_of(node_on_path).expect("node_on_path was reached by both sides");
+
+        SearchStats {
+            from_source: self.from_source.stats(),
+            from_target: self.from_target.stats(),
+            meeting_levels: (source_level, target_level),
+            elapsed: started.elapsed(),
+        }
+    }
+
+    fn solution_through(&self, node_on_path: &TState) -> Solution<TState, TTransition> {
+        join_at(node_on_path, self.from_source.predecessors_of(node_on_path), self.from_target.predecessors_of(node_on_path))
+    }
+}
+
+struct CostDiscoverer<TState, TTransition> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    root: TState,
+    reached_states: HashMap<TState, Reached<TState, TTransition>>,
+
+    /// May contain outdated entries for states which were reached more cheaply later on, those are skipped
+    states_to_settle: BinaryHeap<Queued<TState>>,
+    side: Side,
+    max_level: usize,
+
+    /// Whether a state was not expanded because of `max_level`
+    depth_limited: bool,
+
+    states_per_level: Vec<usize>,
+    peak_frontier_size: usize,
+}
+
+impl<TState, TTransition> CostDiscoverer<TState, TTransition> where
+    TState : WeightedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    fn new(root: TState, estimate: u64, side: Side, max_level: usize) -> CostDiscoverer<TState, TTransition> {
+        let reached_states = HashMap::from([(root.clone(), Reached { cost: 0, level: 0, settled: false, counted: false, predecessor: None })]);
+
+        CostDiscoverer {
+            states_to_settle: BinaryHeap::from([Queued { priority: estimate, cost: 0, state: root.clone() }]),
+            root,
+            reached_states,
+            side,
+            max_level,
+            depth_limited: false,
+            states_per_level: vec![0],
+            peak_frontier_size: 1,
+        }
+    }
+
+    fn root(&self) -> &TState {
+        &self.root
+    }
+
+    /// Lowest priority of the states which still have to be settled, `None` once everything reachable was settled.
+    fn min_priority(&mut self) -> Option<u64> {
+        while let Some(next) = self.states_to_settle.peek() {
+            if !self.is_outdated(next) {
+                return Some(next.priority);
+            }
+
+            self.states_to_settle.pop();
+        }
+
+        None
+    }
+
+    fn is_outdated(&self, queued: &Queued<TState>) -> bool {
+        let reached = &self.reached_states[&queued.state];
+        reached.settled || reached.cost < queued.cost
+    }
+
+    /// Settles the waiting state with the lowest priority and returns all states which became cheaper to reach by doing so.
+    ///
+    /// `estimate` gives the estimated cost from a state to the root of the other side.
+    fn settle_next(&mut self, estimate: impl Fn(&TState) -> u64, observer: &mut impl SearchObserver<TState>) -> Result<Vec<TState>, SearchError> {
+        let Some(Queued { cost, state, .. }) = self.states_to_settle.pop() else {
+            return Ok(vec![]);
+        };
+
+        let reached = self.reached_states.get_mut(&state).expect("only reached states are queued");
+        reached.settled = true;
+        let level = reached.level;
+
+        if !std::mem::replace(&mut reached.counted, true) {
+            if self.states_per_level.len() <= level {
+                self.states_per_level.resize(level + 1, 0);
+            }
+            self.states_per_level[level] += 1;
+        }
+
+        observer.node_expanded(self.side, &state, level);
+
+        if level >= self.max_level {
+            self.depth_limited = true;
+            return Ok(vec![]);
+        }
+
+        let mut improved = vec![];
+
+        for (new_state, transition) in neighbors(&state, self.side)? {
+            let transition_cost = match self.side {
+                Side::Source => state.cost(&transition),
+                Side::Target => new_state.cost(&transition),
+            };
+
+            let Some(new_cost) = cost.checked_add(transition_cost) else {
+                continue;
+            };
+
+            let new_reached = Reached {
+                cost: new_cost,
+                level: level + 1,
+                settled: false,
+                counted: false,
+                predecessor: Some(Predecessor { state: state.clone(), transition }),
+            };
+
+            match self.reached_states.entry(new_state.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(new_reached);
+                },
+                Entry::Occupied(mut entry) => {
+                    // with a heuristic which is admissible but not consistent, even settled states
+                    // can become cheaper and need to be settled again
+                    if entry.get().cost <= new_cost {
+                        continue;
+                    }
+
+                    let counted = entry.get().counted;
+                    entry.insert(Reached { counted, ..new_reached });
+                },
+            }
+
+            let priority = new_cost.saturating_add(estimate(&new_state));
+            self.states_to_settle.push(Queued { priority, cost: new_cost, state: new_state.clone() });
+            improved.push(new_state);
+        }
+
+        self.peak_frontier_size = self.peak_frontier_size.max(self.states_to_settle.len());
+        Ok(improved)
+    }
+
+    /// Lowest known cost to reach `state` from the root.
+    fn cost_of(&self, state: &TState) -> Option<u64> {
+        self.reached_states.get(state).map(|reached| reached.cost)
+    }
+
+    fn level_of(&self, state: &TState) -> Option<usize> {
+        self.reached_states.get(state).map(|reached| reached.level)
+    }
+
+    fn explored_count(&self) -> usize {
+        self.reached_states.len()
+    }
+
+    fn stats(&self) -> SideStats {
+        SideStats {
+            states_per_level: self.states_per_level.clone(),
+            peak_frontier_size: self.peak_frontier_size,
+        }
+    }
+
+    /// Walks from `state` back to the root along the cheapest known way, excluding `state` itself.
+    fn predecessors_of<'a>(&'a self, state: &'a TState) -> impl Iterator<Item = &'a Predecessor<TState, TTransition>> {
+        let mut current = state;
+
+        std::iter::from_fn(move || {
+            let predecessor = self.reached_states[current].predecessor.as_ref()?;
+            current = &predecessor.state;
+            Some(predecessor)
+        })
+    }
+}
+
+struct Reached<TState, TTransition> {
+    cost: u64,
+
+    /// Number of transitions on the cheapest known way from the root
+    level: usize,
+    settled: bool,
+
+    /// Whether it is part of the states per level already, settling it again does not count it twice
+    counted: bool,
+
+    /// `None` only for the root
+    predecessor: Option<Predecessor<TState, TTransition>>,
+}
+
+/// Entry of the priority queue, the state with the lowest priority is the greatest so that it is popped first.
+///
+/// Among states with the same priority, the one which is more expensive to reach comes first. With a
+/// heuristic it is closer to the goal, which avoids exploring lots of equally promising states.
+struct Queued<TState> {
+    priority: u64,
+    cost: u64,
+    state: TState,
+}
+
+impl<TState> PartialEq for Queued<TState> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.cost == other.cost
+    }
+}
+
+impl<TState> Eq for Queued<TState> {}
+
+impl<TState> PartialOrd for Queued<TState> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<TState> Ord for Queued<TState> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority).then(self.cost.cmp(&other.cost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{rngs, Rng, SeedableRng};
+
+    use crate::{state::test::{GraphNode, GridDistance}, State};
+
+    use super::*;
+
+    #[test]
+    fn should_prefer_cheap_detour() {
+        // 0 - 1 - 2 is expensive, 0 - 3 - 4 - 2 is cheap
+        let nodes = GraphNode::weighted(5, &[(0, 1, 10), (1, 2, 10), (0, 3, 1), (3, 4, 1), (4, 2, 1)]);
+
+        let solution = find_cheapest_path(&nodes[0], &nodes[2]).unwrap();
+
+        assert_eq!(solution.transitions(), &[3, 4, 2]);
+        assert_eq!(solution.cost(), 3);
+    }
+
+    #[test]
+    fn should_pick_cheapest_meeting() {
+        // the source side reaches 1 more cheaply, but the way through 2 is cheaper overall
+        let nodes = GraphNode::weighted(4, &[(0, 1, 1), (1, 3, 10), (0, 2, 5), (2, 3, 1)]);
+
+        let solution = find_cheapest_path(&nodes[0], &nodes[3]).unwrap();
+
+        assert_eq!(solution.transitions(), &[2, 3]);
+        assert_eq!(solution.cost(), 6);
+    }
+
+    #[test]
+    fn should_follow_directed_edges() {
+        let nodes = GraphNode::directed(3, &[(0, 1), (1, 2), (2, 0)]);
+
+        assert_eq!(find_cheapest_path(&nodes[0], &nodes[2]).unwrap().transitions(), &[1, 2]);
+        assert_eq!(find_cheapest_path(&nodes[2], &nodes[1]).unwrap().transitions(), &[0, 1]);
+    }
+
+    #[test]
+    fn should_report_unreachable_target() {
+        let nodes = GraphNode::weighted(4, &[(0, 1, 3), (2, 3, 1)]);
+
+        assert_eq!(find_cheapest_path(&nodes[0], &nodes[3]), Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn should_ignore_ways_too_expensive_to_count() {
+        let nodes = GraphNode::weighted(3, &[(0, 1, u64::MAX), (1, 2, 1)]);
+
+        assert_eq!(find_cheapest_path(&nodes[0], &nodes[1]).unwrap().cost(), u64::MAX);
+        assert_eq!(find_cheapest_path(&nodes[0], &nodes[2]), Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn should_count_states_settled_again_once() {
+        // the estimate for 1 delays it, so 3 is settled through the expensive 2 before it gets cheaper through 1
+        let nodes = GraphNode::weighted(4, &[(0, 1, 1), (0, 2, 1), (1, 3, 1), (2, 3, 5)]);
+        let estimate = |state: &GraphNode| if state.index == 1 { 10 } else { 0 };
+        let mut discoverer = CostDiscoverer::new(nodes[0].clone(), 0, Side::Source, usize::MAX);
+
+        while discoverer.min_priority().is_some() {
+            discoverer.settle_next(estimate, &mut NoopObserver).unwrap();
+        }
+
+        assert_eq!(discoverer.cost_of(&nodes[3]), Some(2));
+        assert_eq!(discoverer.stats().states_per_level, vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn should_stop_at_max_depth() {
+        let edges: Vec<_> = (1..10).map(|i| (i - 1, i, 1)).collect();
+        let nodes = GraphNode::weighted(10, &edges);
+        let options = SearchOptions { max_depth: Some(2), ..Default::default() };
+
+        let result = find_cheapest_path_observed(&nodes[0], &nodes[9], &options, &mut NoopObserver);
+
+        assert_eq!(result, Err(SearchError::DepthLimitReached));
+    }
+
+    #[test]
+    fn cost_should_match_dijkstra() {
+        let mut rng = rngs::StdRng::seed_from_u64(42);
+
+        for _ in 0..500 {
+            let nodes = random_weighted_graph(&mut rng);
+            let source = &nodes[rng.random_range(0..nodes.len())];
+            let target = &nodes[rng.random_range(0..nodes.len())];
+
+            match (find_cheapest_path(source, target), dijkstra_cost(source, target)) {
+                (Ok(solution), Some(expected_cost)) => {
+                    assert_eq!(solution.source(), source);
+                    assert_eq!(solution.target(), target);
+                    assert!(solution.steps().all(|(from, transition)| from.get_possible_transitions().any(|t| t == transition)));
+                    assert_eq!(solution.cost(), expected_cost, "{source:?} to {target:?}");
+                },
+                (Err(SearchError::Unreachable), None) => {},
+                (actual, expected) => panic!("{source:?} to {target:?}: got {actual:?}, expected cost {expected:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn heuristic_should_reduce_explored_states() {
+        let nodes = GraphNode::grid(20);
+        let manhattan = GridDistance(20);
+
+        let blind = find_cheapest_path(&nodes[0], &nodes[399]).unwrap();
+        let guided = find_cheapest_path_with_heuristic(&nodes[0], &nodes[399], &manhattan).unwrap();
+
+        assert_eq!(guided.cost(), 38);
+        assert_eq!(blind.cost(), 38);
+        assert!(guided.stats().explored_count() < blind.stats().explored_count() / 2);
+    }
+
+    #[test]
+    fn cost_with_heuristic_should_match_dijkstra() {
+        let mut rng = rngs::StdRng::seed_from_u64(42);
+
+        for _ in 0..500 {
+            let nodes = random_weighted_graph(&mut rng);
+            let source = &nodes[rng.random_range(0..nodes.len())];
+            let target = &nodes[rng.random_range(0..nodes.len())];
+            let heuristic = RandomUnderestimate::new(&nodes, &mut rng);
+
+            match (find_cheapest_path_with_heuristic(source, target, &heuristic), dijkstra_cost(source, target)) {
+                (Ok(solution), Some(expected_cost)) => {
+                    assert_eq!(solution.source(), source);
+                    assert_eq!(solution.target(), target);
+                    assert_eq!(solution.cost(), expected_cost, "{source:?} to {target:?}");
+                },
+                (Err(SearchError::Unreachable), None) => {},
+                (actual, expected) => panic!("{source:?} to {target:?}: got {actual:?}, expected cost {expected:?}"),
+            }
+        }
+    }
+
+    /// Admissible, but usually not consistent: a random fraction of the actual cost, which differs per state.
+    struct RandomUnderestimate {
+        percentages: Vec<u64>,
+    }
+
+    impl RandomUnderestimate {
+        fn new<TRng: Rng>(nodes: &[GraphNode], rng: &mut TRng) -> RandomUnderestimate {
+            RandomUnderestimate { percentages: nodes.iter().map(|_| rng.random_range(0..=100)).collect() }
+        }
+    }
+
+    impl Heuristic<GraphNode> for RandomUnderestimate {
+        fn estimate(&self, from: &GraphNode, to: &GraphNode) -> u64 {
+            // unreachable states would be infinitely far away, so any estimate is fine for them
+            dijkstra_cost(from, to).map_or(0, |cost| cost * self.percentages[from.index] / 100)
+        }
+    }
+
+    fn random_weighted_graph<TRng: Rng>(rng: &mut TRng) -> Vec<GraphNode> {
+        let node_count = rng.random_range(1..40);
+        let edge_count = rng.random_range(0..node_count * 2);
+
+        let edges: Vec<_> = (0..edge_count)
+            .map(|_| (rng.random_range(0..node_count), rng.random_range(0..node_count), rng.random_range(0..10)))
+            .collect();
+
+        GraphNode::weighted(node_count, &edges)
+    }
+
+    /// Plain unidirectional Dijkstra as reference
+    fn dijkstra_cost<TState: WeightedState>(source: &TState, target: &TState) -> Option<u64> {
+        let mut costs = HashMap::from([(source.clone(), 0)]);
+        let mut settled = HashSet::new();
+
+        loop {
+            let (state, cost) = costs.iter()
+                .filter(|(state, _)| !settled.contains(*state))
+                .min_by_key(|(_, cost)| **cost)
+                .map(|(state, cost)| (state.clone(), *cost))?;
+
+            if state == *target {
+                return Some(cost);
+            }
+
+            for transition in state.get_possible_transitions() {
+                let new_cost = cost + state.cost(transition);
+                let known_cost = costs.entry(state.apply(transition)).or_insert(new_cost);
+                *known_cost = new_cost.min(*known_cost);
+            }
+
+            settled.insert(state);
+        }
+    }
+}
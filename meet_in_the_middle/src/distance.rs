@@ -0,0 +1,202 @@
+use std::{collections::HashSet, hash::BuildHasherDefault};
+
+use crate::{layers::{self, Layers}, solver::neighbor_states, visited::BitSet, FastHasher, IndexedState, SearchError, SearchOptions, Side, State};
+
+/// Number of transitions on a shortest way from `source` to `target`, `None` if there is no way at all.
+///
+/// Neither levels nor predecessors are remembered, only which states were seen, so this needs far less
+/// memory than finding the path itself. There is nothing but the shortest distance to find, so `options.mode`
+/// makes no difference.
+pub fn distance<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Option<usize>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    distance_using::<HashSet<_, BuildHasherDefault<FastHasher>>, _, _>(source, target, options)
+}
+
+/// Like [`distance`], remembering the states seen by their [`IndexedState::index`] in a bitset, which takes an
+/// eighth of a byte per index up to the highest one seen instead of a copy of each state.
+pub fn distance_indexed<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Option<usize>, SearchError> where
+    TState : IndexedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    distance_using::<IndexedSeenSet, _, _>(source, target, options)
+}
+
+fn distance_using<TSeen, TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Option<usize>, SearchError> where
+    TSeen : SeenSet<TState>,
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    if source == target {
+        return Ok(Some(0));
+    }
+
+    let max_level = options.max_depth.unwrap_or(usize::MAX);
+    let mut from_source = DistanceLayers::<_, TSeen>::new(source.clone(), Side::Source);
+    let mut from_target = DistanceLayers::<_, TSeen>::new(target.clone(), Side::Target);
+
+    match layers::meet(&mut from_source, &mut from_target, max_level, options) {
+        Ok(()) => Ok(Some(from_source.level + from_target.level)),
+        Err(SearchError::Unreachable) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Which states one side of [`distance`] has seen, without numbering them like a [`crate::VisitedSet`].
+trait SeenSet<TState> : Default {
+    /// Returns whether the state was not seen before.
+    fn insert(&mut self, state: &TState) -> bool;
+
+    fn contains(&self, state: &TState) -> bool;
+
+    fn len(&self) -> usize;
+}
+
+impl<TState> SeenSet<TState> for HashSet<TState, BuildHasherDefault<FastHasher>> where
+    TState : State,
+{
+    fn insert(&mut self, state: &TState) -> bool {
+        !self.contains(state) && HashSet::insert(self, state.clone())
+    }
+
+    fn contains(&self, state: &TState) -> bool {
+        HashSet::contains(self, state)
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+}
+
+/// The indexes seen in a [`BitSet`], counted as it has no length of its own.
+#[derive(Default)]
+struct IndexedSeenSet {
+    bits: BitSet,
+    len: usize,
+}
+
+impl<TState> SeenSet<TState> for IndexedSeenSet where
+    TState : IndexedState,
+{
+    fn insert(&mut self, state: &TState) -> bool {
+        let is_new = self.bits.insert(state.index());
+        self.len += usize::from(is_new);
+        is_new
+    }
+
+    fn contains(&self, state: &TState) -> bool {
+        self.bits.contains(state.index())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// The states seen by one side of [`distance`] and the ones at its current level.
+struct DistanceLayers<TState, TSeen> {
+    seen: TSeen,
+    frontier: Vec<TState>,
+    side: Side,
+    level: usize,
+}
+
+impl<TState, TSeen> DistanceLayers<TState, TSeen> where
+    TSeen : SeenSet<TState>,
+{
+    fn new(root: TState, side: Side) -> DistanceLayers<TState, TSeen> {
+        let mut seen = TSeen::default();
+        seen.insert(&root);
+
+        DistanceLayers { seen, frontier: vec![root], side, level: 0 }
+    }
+}
+
+impl<TState, TTransition, TSeen> Layers for DistanceLayers<TState, TSeen> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+    TSeen : SeenSet<TState>,
+{
+    /// Only the levels of both sides matter, not where they met
+    type Meeting = ();
+
+    fn level(&self) -> usize {
+        self.level
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.frontier.len()
+    }
+
+    fn seen_count(&self) -> usize {
+        self.seen.len()
+    }
+
+    fn expand(&mut self, other: &Self, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<Option<()>, SearchError> {
+        let mut next_frontier = vec![];
+        self.level += 1;
+
+        for state in std::mem::take(&mut self.frontier) {
+            for neighbor in neighbor_states(&state, self.side)? {
+                if other.seen.contains(&neighbor) {
+                    return Ok(Some(()));
+                }
+
+                if self.seen.insert(&neighbor) {
+                    next_frontier.push(neighbor);
+                    check_limits(self.seen.len())?;
+                }
+            }
+        }
+
+        self.frontier = next_frontier;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::{find_path_with, state::test::{for_random_pairs, GraphNode}, SearchMode, Strategy};
+
+    use super::*;
+
+    #[test]
+    fn should_measure_distance() {
+        let nodes = GraphNode::grid(10);
+
+        assert_eq!(distance(&nodes[0], &nodes[99], &SearchOptions::default()), Ok(Some(18)));
+        assert_eq!(distance(&nodes[45], &nodes[45], &SearchOptions::default()), Ok(Some(0)));
+    }
+
+    #[test]
+    fn should_respect_limits() {
+        let nodes = GraphNode::grid(10);
+
+        let shallow = SearchOptions { max_depth: Some(3), ..Default::default() };
+        assert_eq!(distance(&nodes[0], &nodes[99], &shallow), Err(SearchError::DepthLimitReached));
+
+        let expired = SearchOptions { deadline: Some(Instant::now()), ..Default::default() };
+        assert_eq!(distance(&nodes[0], &nodes[99], &expired), Err(SearchError::DeadlineExceeded));
+    }
+
+    #[test]
+    fn distance_should_match_shortest_path() {
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+        let smaller_frontier = SearchOptions { strategy: Strategy::SmallerFrontier, ..Default::default() };
+
+        for_random_pairs(1000, |source, target| {
+            let expected = match find_path_with(source, target, &options) {
+                Ok(solution) => Some(solution.len()),
+                Err(SearchError::Unreachable) => None,
+                Err(error) => panic!("unexpected {error:?}"),
+            };
+
+            assert_eq!(distance(source, target, &options), Ok(expected), "from {source:?} to {target:?}");
+            assert_eq!(distance(source, target, &smaller_frontier), Ok(expected), "from {source:?} to {target:?}");
+            assert_eq!(distance_indexed(source, target, &options), Ok(expected), "from {source:?} to {target:?}");
+        });
+    }
+}
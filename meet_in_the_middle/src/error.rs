@@ -0,0 +1,50 @@
+use std::{error::Error, fmt::Display, io::ErrorKind};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SearchError {
+    /// One side explored everything reachable from it without meeting the other side
+    Unreachable,
+
+    /// Both sides reached [`crate::SearchOptions::max_depth`] without meeting
+    DepthLimitReached,
+
+    /// More states than [`crate::SearchOptions::max_states`] would have been needed
+    StateLimitReached,
+
+    /// [`crate::SearchOptions::deadline`] passed before the sides met, the clock is only looked at once every
+    /// 1024 states, so the search can run slightly longer
+    DeadlineExceeded,
+
+    /// [`crate::SearchOptions::cancellation`] was set to `true` before the sides met, which is also only looked at
+    /// once every 1024 states
+    Cancelled,
+
+    /// A neighbor of a state on the target side has no transition leading back to it, which happens
+    /// if transitions are not reversible and [`crate::State::get_predecessors`] is not overridden
+    NotReversible,
+
+    /// Reading or writing the files of [`crate::find_path_external`] failed
+    Io(ErrorKind),
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::Unreachable => write!(f, "target is not reachable from source"),
+            SearchError::DepthLimitReached => write!(f, "no path found within the maximum depth"),
+            SearchError::StateLimitReached => write!(f, "no path found within the maximum number of states"),
+            SearchError::DeadlineExceeded => write!(f, "no path found before the deadline"),
+            SearchError::Cancelled => write!(f, "search was cancelled"),
+            SearchError::NotReversible => write!(f, "a predecessor has no transition leading to its state"),
+            SearchError::Io(kind) => write!(f, "search failed to use its files: {kind}"),
+        }
+    }
+}
+
+impl Error for SearchError {}
+
+impl From<std::io::Error> for SearchError {
+    fn from(error: std::io::Error) -> Self {
+        SearchError::Io(error.kind())
+    }
+}
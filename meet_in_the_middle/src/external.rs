@@ -0,0 +1,442 @@
+use std::{cmp::Reverse, collections::BinaryHeap, fs::{self, File}, io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write}, marker::PhantomData, path::PathBuf, process, sync::atomic::{AtomicUsize, Ordering}, time::Instant};
+
+use crate::{layers::{self, Layers}, path::Solution, solver::{neighbor_states, neighbors}, PackedKey, PackedState, SearchError, SearchOptions, SearchStats, Side, SideStats};
+
+/// Where [`find_path_external`] keeps its files and how many states it holds in memory at once.
+#[derive(Debug, Clone)]
+pub struct ExternalStorage {
+    /// The search creates a directory of its own in here and removes it once it is done
+    pub directory: PathBuf,
+
+    /// Number of packed states collected in memory before they are sorted and written to a file
+    pub run_length: usize,
+}
+
+impl ExternalStorage {
+    pub fn new(directory: impl Into<PathBuf>) -> ExternalStorage {
+        ExternalStorage { directory: directory.into(), run_length: 1 << 20 }
+    }
+}
+
+impl Default for ExternalStorage {
+    fn default() -> Self {
+        ExternalStorage::new(std::env::temp_dir())
+    }
+}
+
+/// Finds a shortest way from `source` to `target`, keeping the levels of both sides in files instead of memory.
+///
+/// Each level is written to a file of sorted packed states, see [`PackedState`]. The neighbors of a level are
+/// sorted in runs of [`ExternalStorage::run_length`] states, then the runs are merged while dropping every state
+/// found in one of the earlier levels. The sides met once the new level and the last level of the other side
+/// share a state, the way back is found by looking up neighbors in the level before. Since only complete levels
+/// end up in files, `options.mode` does not matter. Fails with [`SearchError::Io`] if the files cannot be written or read.
+pub fn find_path_external<TState, TTransition>(source: &TState, target: &TState, storage: &ExternalStorage, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : PackedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let started = Instant::now();
+    let max_level = options.max_depth.unwrap_or(usize::MAX);
+    let mut from_source = ExternalLayers::new(source, Side::Source, storage)?;
+    let mut from_target = ExternalLayers::new(target, Side::Target, storage)?;
+
+    let meeting = if source == target {
+        source.pack()
+    } else {
+        layers::meet(&mut from_source, &mut from_target, max_level, options)?
+    };
+
+    let stats = SearchStats {
+        from_source: from_source.stats(),
+        from_target: from_target.stats(),
+        meeting_levels: (from_source.level(), from_target.level()),
+        elapsed: started.elapsed(),
+    };
+
+    let meeting = source.unpack(meeting);
+    Ok(layers::join_ways(meeting.clone(), from_source.way_back(meeting.clone())?, from_target.way_back(meeting)?).with_stats(stats))
+}
+
+/// The smallest state in both files.
+fn first_common<TPacked: PackedKey>(file: &SortedFile<TPacked>, other_file: &SortedFile<TPacked>) -> io::Result<Option<TPacked>> {
+    let mut reader = file.reader()?;
+    let mut other_reader = other_file.reader()?;
+
+    while let (Some(packed), Some(other_packed)) = (reader.current, other_reader.current) {
+        match packed.cmp(&other_packed) {
+            std::cmp::Ordering::Less => reader.advance()?,
+            std::cmp::Ordering::Greater => other_reader.advance()?,
+            std::cmp::Ordering::Equal => return Ok(Some(packed)),
+        }
+    }
+
+    Ok(None)
+}
+
+/// The levels of one side of [`find_path_external`], one file each in a directory of its own.
+struct ExternalLayers<TState> where
+    TState : PackedState,
+{
+    layers: Vec<SortedFile<TState::Packed>>,
+    seen_count: usize,
+    side: Side,
+    directory: WorkingDirectory,
+    run_length: usize,
+
+    /// Used to unpack states
+    prototype: TState,
+}
+
+impl<TState, TTransition> ExternalLayers<TState> where
+    TState : PackedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    fn new(root: &TState, side: Side, storage: &ExternalStorage) -> io::Result<ExternalLayers<TState>> {
+        let mut directory = WorkingDirectory::create(storage)?;
+        let mut writer = KeyWriter::create(directory.next_file())?;
+        writer.push(root.pack())?;
+
+        Ok(ExternalLayers {
+            layers: vec![writer.finish()?],
+            seen_count: 1,
+            side,
+            directory,
+            run_length: storage.run_length.max(1),
+            prototype: root.clone(),
+        })
+    }
+
+    fn last(&self) -> &SortedFile<TState::Packed> {
+        self.layers.last().expect("there is always the level of the root")
+    }
+
+    /// Writes the next level, all neighbors of the last level which are in none of the levels so far.
+    fn write_level(&mut self, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<(), SearchError> {
+        let mut runs = vec![];
+        let mut run = Vec::with_capacity(self.run_length);
+        let mut frontier = self.last().reader()?;
+
+        while let Some(packed) = frontier.current {
+            let state = self.prototype.unpack(packed);
+
+            run.extend(neighbor_states(&state, self.side)?.into_iter().map(|neighbor| neighbor.pack()));
+
+            if run.len() >= self.run_length {
+                runs.push(write_run(&mut run, self.directory.next_file())?);
+            }
+
+            frontier.advance()?;
+        }
+
+        if !run.is_empty() {
+            runs.push(write_run(&mut run, self.directory.next_file())?);
+        }
+
+        let mut earlier_levels = self.layers.iter().map(SortedFile::reader).collect::<io::Result<Vec<_>>>()?;
+        let mut writer = KeyWriter::create(self.directory.next_file())?;
+
+        merge_runs(&runs, |packed| {
+            for level in &mut earlier_levels {
+                if level.skip_to(packed)? {
+                    return Ok(());
+                }
+            }
+
+            writer.push(packed)?;
+            self.seen_count += 1;
+            check_limits(self.seen_count)
+        })?;
+
+        for run in runs {
+            fs::remove_file(run.path)?;
+        }
+
+        self.layers.push(writer.finish()?);
+        Ok(())
+    }
+
+    /// The states leading from `state` back to the root, each with the transition between it and the one before in forward direction.
+    ///
+    /// `state` has to be in the last level.
+    fn way_back(&self, state: TState) -> Result<Vec<(TState, TTransition)>, SearchError> {
+        let mut way = vec![];
+        let mut current = state;
+        let backwards = match self.side {
+            Side::Source => Side::Target,
+            Side::Target => Side::Source,
+        };
+
+        for level in self.layers[..self.level()].iter().rev() {
+            let mut step = None;
+
+            for (neighbor, transition) in neighbors(&current, backwards)? {
+                if level.contains(neighbor.pack())? {
+                    step = Some((neighbor, transition));
+                    break;
+                }
+            }
+
+            let (next, transition) = step.ok_or(SearchError::NotReversible)?;
+            way.push((next.clone(), transition));
+            current = next;
+        }
+
+        Ok(way)
+    }
+
+    fn stats(&self) -> SideStats {
+        SideStats {
+            states_per_level: self.layers.iter().map(|layer| layer.len).collect(),
+            peak_frontier_size: self.layers.iter().map(|layer| layer.len).max().unwrap_or(0),
+        }
+    }
+}
+
+impl<TState, TTransition> Layers for ExternalLayers<TState> where
+    TState : PackedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    type Meeting = TState::Packed;
+
+    fn level(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.last().len
+    }
+
+    fn seen_count(&self) -> usize {
+        self.seen_count
+    }
+
+    /// The new level is written completely before it is compared with the last level of `other`.
+    fn expand(&mut self, other: &Self, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<Option<TState::Packed>, SearchError> {
+        self.write_level(check_limits)?;
+        Ok(first_common(self.last(), other.last())?)
+    }
+}
+
+/// Sorts `run`, writes it to `path` without duplicates and empties it.
+fn write_run<TPacked: PackedKey>(run: &mut Vec<TPacked>, path: PathBuf) -> io::Result<SortedFile<TPacked>> {
+    run.sort_unstable();
+    run.dedup();
+
+    let mut writer = KeyWriter::create(path)?;
+
+    for packed in run.drain(..) {
+        writer.push(packed)?;
+    }
+
+    writer.finish()
+}
+
+/// Calls `handle` for every state in any of the `runs`, once each and in ascending order.
+fn merge_runs<TPacked: PackedKey>(runs: &[SortedFile<TPacked>], mut handle: impl FnMut(TPacked) -> Result<(), SearchError>) -> Result<(), SearchError> {
+    let mut readers = runs.iter().map(SortedFile::reader).collect::<io::Result<Vec<_>>>()?;
+    let mut smallest: BinaryHeap<_> = readers.iter().enumerate()
+        .filter_map(|(index, reader)| reader.current.map(|packed| Reverse((packed, index))))
+        .collect();
+    let mut previous = None;
+
+    while let Some(Reverse((packed, index))) = smallest.pop() {
+        if previous != Some(packed) {
+            handle(packed)?;
+            previous = Some(packed);
+        }
+
+        readers[index].advance()?;
+
+        if let Some(next) = readers[index].current {
+            smallest.push(Reverse((next, index)));
+        }
+    }
+
+    Ok(())
+}
+
+/// A file of distinct packed states in ascending order, [`PackedKey::BYTES`] bytes each.
+struct SortedFile<TPacked> {
+    path: PathBuf,
+    len: usize,
+    packed: PhantomData<TPacked>,
+}
+
+impl<TPacked> SortedFile<TPacked> where
+    TPacked : PackedKey,
+{
+    fn reader(&self) -> io::Result<KeyReader<TPacked>> {
+        let mut reader = KeyReader {
+            reader: BufReader::new(File::open(&self.path)?),
+            remaining: self.len,
+            current: None,
+            bytes: vec![0; TPacked::BYTES],
+        };
+
+        reader.advance()?;
+        Ok(reader)
+    }
+
+    /// Whether `packed` is in the file, found by binary search.
+    fn contains(&self, packed: TPacked) -> io::Result<bool> {
+        let mut file = File::open(&self.path)?;
+        let mut bytes = vec![0; TPacked::BYTES];
+        let (mut low, mut high) = (0, self.len);
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+            file.seek(SeekFrom::Start((middle * TPacked::BYTES) as u64))?;
+            file.read_exact(&mut bytes)?;
+
+            match TPacked::read_bytes(&bytes).cmp(&packed) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Ok(true),
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Reads a [`SortedFile`] front to back, `current` is `None` once all states were read.
+struct KeyReader<TPacked> {
+    reader: BufReader<File>,
+    remaining: usize,
+    current: Option<TPacked>,
+    bytes: Vec<u8>,
+}
+
+impl<TPacked> KeyReader<TPacked> where
+    TPacked : PackedKey,
+{
+    fn advance(&mut self) -> io::Result<()> {
+        self.current = if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            self.reader.read_exact(&mut self.bytes)?;
+            Some(TPacked::read_bytes(&self.bytes))
+        };
+
+        Ok(())
+    }
+
+    /// Skips the states below `packed`, returns whether `packed` is in the file.
+    fn skip_to(&mut self, packed: TPacked) -> io::Result<bool> {
+        while self.current.is_some_and(|current| current < packed) {
+            self.advance()?;
+        }
+
+        Ok(self.current == Some(packed))
+    }
+}
+
+/// Writes a [`SortedFile`], the states have to be pushed in ascending order.
+struct KeyWriter<TPacked> {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    len: usize,
+    bytes: Vec<u8>,
+    packed: PhantomData<TPacked>,
+}
+
+impl<TPacked> KeyWriter<TPacked> where
+    TPacked : PackedKey,
+{
+    fn create(path: PathBuf) -> io::Result<KeyWriter<TPacked>> {
+        Ok(KeyWriter {
+            writer: BufWriter::new(File::create(&path)?),
+            path,
+            len: 0,
+            bytes: vec![0; TPacked::BYTES],
+            packed: PhantomData,
+        })
+    }
+
+    fn push(&mut self, packed: TPacked) -> io::Result<()> {
+        packed.write_bytes(&mut self.bytes);
+        self.len += 1;
+        self.writer.write_all(&self.bytes)
+    }
+
+    fn finish(mut self) -> io::Result<SortedFile<TPacked>> {
+        self.writer.flush()?;
+
+        Ok(SortedFile { path: self.path, len: self.len, packed: PhantomData })
+    }
+}
+
+/// A directory only used by one search, removed with everything in it when dropped.
+struct WorkingDirectory {
+    path: PathBuf,
+    files: usize,
+}
+
+impl WorkingDirectory {
+    fn create(storage: &ExternalStorage) -> io::Result<WorkingDirectory> {
+        static SEARCHES: AtomicUsize = AtomicUsize::new(0);
+
+        let search = SEARCHES.fetch_add(1, Ordering::Relaxed);
+        let path = storage.directory.join(format!("meet_in_the_middle-{}-{search}", process::id()));
+
+        fs::create_dir_all(&storage.directory)?;
+        fs::create_dir(&path)?;
+
+        Ok(WorkingDirectory { path, files: 0 })
+    }
+
+    fn next_file(&mut self) -> PathBuf {
+        self.files += 1;
+        self.path.join(format!("{}.states", self.files))
+    }
+}
+
+impl Drop for WorkingDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::state::test::{assert_matches_shortest_path, GraphNode};
+
+    use super::*;
+
+    #[test]
+    fn should_find_shortest_path() {
+        let nodes = GraphNode::grid(10);
+        let storage = ExternalStorage::default();
+
+        let solution = find_path_external(&nodes[0], &nodes[99], &storage, &SearchOptions::default()).unwrap();
+        assert_eq!(solution.len(), 18);
+        assert_eq!(solution.stats().meeting_levels, (9, 9));
+
+        let trivial = find_path_external(&nodes[45], &nodes[45], &storage, &SearchOptions::default()).unwrap();
+        assert_eq!(trivial.states(), &[nodes[45].clone()]);
+    }
+
+    #[test]
+    fn should_respect_limits_and_clean_up() {
+        let nodes = GraphNode::grid(10);
+        let storage = ExternalStorage::new(std::env::temp_dir().join(format!("meet_in_the_middle-limits-{}", process::id())));
+
+        let shallow = SearchOptions { max_depth: Some(3), ..Default::default() };
+        assert_eq!(find_path_external(&nodes[0], &nodes[99], &storage, &shallow), Err(SearchError::DepthLimitReached));
+
+        let expired = SearchOptions { deadline: Some(Instant::now()), ..Default::default() };
+        assert_eq!(find_path_external(&nodes[0], &nodes[99], &storage, &expired), Err(SearchError::DeadlineExceeded));
+
+        assert_eq!(fs::read_dir(&storage.directory).unwrap().count(), 0);
+        fs::remove_dir(&storage.directory).unwrap();
+    }
+
+    #[test]
+    fn external_path_should_match_shortest_path() {
+        let storage = ExternalStorage { run_length: 3, ..Default::default() };
+        assert_matches_shortest_path(200, |source, target| find_path_external(source, target, &storage, &SearchOptions::default()));
+    }
+}
@@ -0,0 +1,240 @@
+use std::{collections::HashMap, time::Instant};
+#[cfg(feature = "serde")]
+use std::{io, path::Path};
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::checkpoint::{load_versioned, save_versioned};
+use crate::{find_path_to_goal, path::Solution, solver::neighbors, SearchError, SearchOptions, SearchStats, Side, SideStats, State};
+
+/// Every state within a fixed number of transitions of a target, each with its way to the target.
+///
+/// Building it does the search from the target side once, which every [`crate::find_path`] to the same
+/// target would repeat. Afterwards [`PrecomputedGoal::find_path`] only searches from the source until
+/// it reaches a state in the table.
+pub struct PrecomputedGoal<TState, TTransition> {
+    target: TState,
+    table: HashMap<TState, GoalEntry<TTransition>>,
+
+    /// Whether the table holds every state which can reach the target
+    complete: bool,
+
+    states_per_level: Vec<usize>,
+}
+
+struct GoalEntry<TTransition> {
+    level: usize,
+
+    /// Applying it leads to the state one transition closer to the target, `None` for the target itself
+    transition: Option<TTransition>,
+}
+
+impl<TState, TTransition> PrecomputedGoal<TState, TTransition> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    /// Explores every state from which `target` can be reached with `depth` transitions or fewer.
+    pub fn new(target: &TState, depth: usize) -> Result<PrecomputedGoal<TState, TTransition>, SearchError> {
+        let mut table = HashMap::from([(target.clone(), GoalEntry { level: 0, transition: None })]);
+        let mut frontier = vec![target.clone()];
+        let mut states_per_level = vec![1];
+
+        while states_per_level.len() <= depth && !frontier.is_empty() {
+            let level = states_per_level.len();
+            let mut next_frontier = vec![];
+
+            for state in frontier {
+                for (predecessor, transition) in neighbors(&state, Side::Target)? {
+                    if !table.contains_key(&predecessor) {
+                        table.insert(predecessor.clone(), GoalEntry { level, transition: Some(transition) });
+                        next_frontier.push(predecessor);
+                    }
+                }
+            }
+
+            if !next_frontier.is_empty() {
+                states_per_level.push(next_frontier.len());
+            }
+
+            frontier = next_frontier;
+        }
+
+        Ok(PrecomputedGoal {
+            target: target.clone(),
+            table,
+            complete: frontier.is_empty(),
+            states_per_level,
+        })
+    }
+
+    pub fn target(&self) -> &TState {
+        &self.target
+    }
+
+    /// Number of states in the table.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Number of transitions from `state` to the target, `None` unless `state` is in the table.
+    pub fn distance(&self, state: &TState) -> Option<usize> {
+        self.table.get(state).map(|entry| entry.level)
+    }
+
+    /// Finds a shortest way from `source` to the target, searching from `source` until the table is reached.
+    ///
+    /// All states at the deepest level of the table are as far from the target as possible, so the first
+    /// one reached lies on a shortest way. `options.max_depth` and `options.max_states` only limit the
+    /// search from the source, `options.mode` and `options.strategy` are ignored.
+    pub fn find_path(&self, source: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> {
+        let started = Instant::now();
+
+        if self.complete && !self.table.contains_key(source) {
+            return Err(SearchError::Unreachable);
+        }
+
+        let to_table = find_path_to_goal(source, |state| self.table.contains_key(state), options)?;
+        let mut states = to_table.states().to_vec();
+        let mut transitions = to_table.transitions().to_vec();
+        self.extend_to_target(&mut states, &mut transitions);
+
+        let stats = SearchStats {
+            from_source: to_table.stats().from_source.clone(),
+            from_target: self.stats(),
+            meeting_levels: (to_table.len(), self.table[to_table.target()].level),
+            elapsed: started.elapsed(),
+        };
+
+        Ok(Solution::new(states, transitions).with_stats(stats))
+    }
+
+    /// Number of transitions from the deepest states in the table to the target.
+    pub fn depth(&self) -> usize {
+        self.states_per_level.len() - 1
+    }
+
+    /// Whether the table holds every state which can reach the target, so no state missing from it can.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Follows the table from the last of `states`, which has to be in it, to the target.
+    pub(crate) fn extend_to_target(&self, states: &mut Vec<TState>, transitions: &mut Vec<TTransition>) {
+        let mut current = states.last().expect("a way has at least one state").clone();
+
+        while let Some(transition) = &self.table[&current].transition {
+            current = current.apply(transition);
+            states.push(current.clone());
+            transitions.push(transition.clone());
+        }
+    }
+
+    /// The work done to build the table, the same for every path found with it.
+    pub fn stats(&self) -> SideStats {
+        SideStats {
+            states_per_level: self.states_per_level.clone(),
+            peak_frontier_size: self.states_per_level.iter().copied().max().unwrap_or(0),
+        }
+    }
+}
+
+/// Starts every file written by [`PrecomputedGoal::save`], followed by [`GOAL_VERSION`].
+#[cfg(feature = "serde")]
+const GOAL_MAGIC: &[u8; 8] = b"MITMGOAL";
+
+/// Changes whenever [`SavedGoal`] changes, files of other versions are rejected.
+#[cfg(feature = "serde")]
+const GOAL_VERSION: u32 = 1;
+
+/// A [`PrecomputedGoal`] on disk, saved with references and loaded with owned states.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SavedGoal<TState, TTransition> {
+    target: TState,
+
+    /// Each state with its level and its transition towards the target
+    entries: Vec<(TState, usize, Option<TTransition>)>,
+
+    complete: bool,
+    states_per_level: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<TState, TTransition> PrecomputedGoal<TState, TTransition> where
+    TState : State<Transition = TTransition> + Serialize + DeserializeOwned,
+    TTransition : Clone + Serialize + DeserializeOwned,
+{
+    /// Writes the table to `path` in a versioned format, see [`PrecomputedGoal::load`].
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let saved = SavedGoal {
+            target: &self.target,
+            entries: self.table.iter().map(|(state, entry)| (state, entry.level, entry.transition.as_ref())).collect(),
+            complete: self.complete,
+            states_per_level: self.states_per_level.clone(),
+        };
+
+        save_versioned(&saved, GOAL_MAGIC, GOAL_VERSION, path.as_ref())
+    }
+
+    /// Reads a table written by [`PrecomputedGoal::save`], fails with [`io::ErrorKind::InvalidData`]
+    /// if the file was written by another version or for different types of states.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<PrecomputedGoal<TState, TTransition>> {
+        let saved: SavedGoal<TState, TTransition> = load_versioned(GOAL_MAGIC, GOAL_VERSION, path.as_ref())?;
+
+        Ok(PrecomputedGoal {
+            target: saved.target,
+            table: saved.entries.into_iter().map(|(state, level, transition)| (state, GoalEntry { level, transition })).collect(),
+            complete: saved.complete,
+            states_per_level: saved.states_per_level,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::state::test::{assert_shortest, for_random_graphs, random_node, GraphNode};
+
+    use super::*;
+
+    #[test]
+    fn should_search_forward_until_table() {
+        let nodes = GraphNode::grid(10);
+        let goal = PrecomputedGoal::new(&nodes[99], 3).unwrap();
+        assert_eq!(goal.len(), 10);
+        assert_eq!(goal.depth(), 3);
+        assert_eq!(goal.distance(&nodes[89]), Some(1));
+        assert_eq!(goal.distance(&nodes[0]), None);
+
+        let solution = goal.find_path(&nodes[0], &SearchOptions::default()).unwrap();
+        assert_eq!(solution.len(), 18);
+        assert_eq!(solution.target(), &nodes[99]);
+        assert_eq!(solution.stats().meeting_levels, (15, 3));
+
+        let inside = goal.find_path(&nodes[98], &SearchOptions::default()).unwrap();
+        assert_eq!(inside.len(), 1);
+
+        let shallow = SearchOptions { max_depth: Some(14), ..Default::default() };
+        assert_eq!(goal.find_path(&nodes[0], &shallow).map(|solution| solution.len()), Err(SearchError::DepthLimitReached));
+    }
+
+    #[test]
+    fn precomputed_path_should_match_shortest_path() {
+        for_random_graphs(1000, 40, 2, |nodes, rng| {
+            let target = random_node(rng, nodes);
+            let goal = PrecomputedGoal::new(target, rng.random_range(0..4)).unwrap();
+
+            for _ in 0..3 {
+                let source = random_node(rng, nodes);
+                assert_shortest(goal.find_path(source, &SearchOptions::default()), source, target);
+            }
+        });
+    }
+}
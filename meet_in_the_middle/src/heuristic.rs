@@ -0,0 +1,17 @@
+/// Estimates the cost of getting from one state to another, see [`crate::find_cheapest_path_with_heuristic`].
+///
+/// Found paths are only guaranteed to be the cheapest if the estimate is admissible, i.e. never higher
+/// than the actual cost. States found from the source are estimated towards the target, states found
+/// from the target are estimated from the source.
+pub trait Heuristic<TState> {
+    fn estimate(&self, from: &TState, to: &TState) -> u64;
+}
+
+/// Never estimates anything but zero, e.g. turning bidirectional A* into plain bidirectional Dijkstra.
+pub(crate) struct NoHeuristic;
+
+impl<TState> Heuristic<TState> for NoHeuristic {
+    fn estimate(&self, _from: &TState, _to: &TState) -> u64 {
+        0
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{options::Limits, path::Solution, SearchError, SearchOptions, Strategy};
+
+/// One side of a search which only keeps what its storage needs about complete levels, see [`meet`].
+pub(crate) trait Layers {
+    /// Whatever identifies the state both sides met at
+    type Meeting;
+
+    /// Number of levels expanded so far.
+    fn level(&self) -> usize;
+
+    /// Number of states in the last level.
+    fn frontier_size(&self) -> usize;
+
+    /// Number of states seen on all levels.
+    fn seen_count(&self) -> usize;
+
+    /// Moves on to the next level and returns a state of it which `other` has seen, if there is one.
+    ///
+    /// `check_limits` is called with [`Layers::seen_count`] whenever new states were seen.
+    fn expand(&mut self, other: &Self, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<Option<Self::Meeting>, SearchError>;
+}
+
+/// Expands complete levels from the sides picked by `options.strategy` until they meet, no side goes beyond `max_level`.
+///
+/// Before a level is expanded, no state up to the current level of this side was seen by the other side, so
+/// a state of the new level seen by the other side was found at its own current level. All states where the
+/// sides meet this way lie on shortest ways, so the first one found is as good as any other. Fails with
+/// [`SearchError::Unreachable`] once either side has no states left to expand.
+pub(crate) fn meet<TLayers: Layers>(from_source: &mut TLayers, from_target: &mut TLayers, max_level: usize, options: &SearchOptions) -> Result<TLayers::Meeting, SearchError> {
+    let mut limits = Limits::new(options);
+    let mut expand_source = true;
+
+    loop {
+        match (from_source.level() >= max_level, from_target.level() >= max_level) {
+            (true, true) => return Err(SearchError::DepthLimitReached),
+            (true, false) => expand_source = false,
+            (false, true) => expand_source = true,
+            (false, false) => if options.strategy == Strategy::SmallerFrontier {
+                expand_source = from_source.frontier_size() <= from_target.frontier_size();
+            },
+        }
+
+        let (layers, other_layers) = if expand_source {
+            (&mut *from_source, &*from_target)
+        } else {
+            (&mut *from_target, &*from_source)
+        };
+
+        let other_seen_count = other_layers.seen_count();
+
+        if let Some(meeting) = layers.expand(other_layers, &mut |seen_count| limits.check(seen_count + other_seen_count))? {
+            return Ok(meeting);
+        }
+
+        if layers.frontier_size() == 0 {
+            return Err(SearchError::Unreachable);
+        }
+
+        expand_source = !expand_source;
+    }
+}
+
+/// The solution through `meeting`, given the ways from it back to where each side started.
+///
+/// Each state on the ways comes with the transition between it and the one before in forward direction.
+pub(crate) fn join_ways<TState, TTransition>(meeting: TState, to_source: Vec<(TState, TTransition)>, to_target: Vec<(TState, TTransition)>) -> Solution<TState, TTransition> {
+    let mut states = vec![meeting];
+    let mut transitions = vec![];
+
+    for (predecessor, transition) in to_source {
+        states.push(predecessor);
+        transitions.push(transition);
+    }
+
+    states.reverse();
+    transitions.reverse();
+
+    for (successor, transition) in to_target {
+        states.push(successor);
+        transitions.push(transition);
+    }
+
+    Solution::new(states, transitions)
+}
@@ -1,8 +1,49 @@
-/// Inspired by https://www.youtube.com/watch?v=wL3uWO-KLUE
+//! Inspired by https://www.youtube.com/watch?v=wL3uWO-KLUE
 
+mod all_paths;
+#[cfg(feature = "serde")]
+mod checkpoint;
+mod deepening;
+mod dijkstra;
+mod distance;
+mod error;
+mod external;
+mod goal;
+mod heuristic;
+mod layers;
+mod observer;
+mod options;
+mod packed;
+#[cfg(feature = "parallel")]
+mod parallel;
 mod path;
+mod ranked;
 mod state;
 mod solver;
+mod stats;
+mod visited;
 
-pub use state::State;
-pub use solver::{find_path, find_nodes_on_path};
\ No newline at end of file
+pub use all_paths::{all_shortest_paths, count_shortest_paths, AllShortestPaths};
+#[cfg(feature = "serde")]
+pub use checkpoint::{find_path_checkpointed, resume_from, Checkpoint};
+pub use dijkstra::{find_cheapest_path, find_cheapest_path_observed, find_cheapest_path_with_heuristic, find_cheapest_path_with_heuristic_observed};
+pub use distance::{distance, distance_indexed};
+pub use error::SearchError;
+pub use external::{find_path_external, ExternalStorage};
+pub use goal::PrecomputedGoal;
+pub use heuristic::Heuristic;
+pub use observer::{NoopObserver, SearchObserver, Side, StdoutObserver};
+#[cfg(feature = "log")]
+pub use observer::LogObserver;
+#[cfg(feature = "tracing")]
+pub use observer::TracingObserver;
+pub use options::{SearchMode, SearchOptions, Strategy};
+pub use packed::find_path_packed;
+#[cfg(feature = "parallel")]
+pub use parallel::{find_path_parallel, find_path_parallel_observed};
+pub use path::{Path, Solution};
+pub use ranked::find_path_ranked;
+pub use state::{IndexedState, PackedKey, PackedState, RankedState, State, WeightedState};
+pub use stats::{SearchStats, SideStats};
+pub use solver::{Discoverer, find_path, find_nodes_on_path, find_path_observed, find_path_between_any, find_path_to_any, find_path_to_goal, find_path_using, find_path_with, find_shortest_path, find_transitions};
+pub use visited::{FastHasher, FastVisitedSet, HashVisitedSet, IndexedVisitedSet, VisitedSet};
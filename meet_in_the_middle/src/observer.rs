@@ -0,0 +1,108 @@
+use std::fmt::Debug;
+
+use crate::SearchError;
+
+/// The side of a bidirectional search an event happened on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Source,
+    Target,
+}
+
+/// Gets notified about the progress of a search, all methods do nothing by default.
+pub trait SearchObserver<TState> {
+    /// All states `level` transitions away from the start of `side` are known now.
+    fn level_finished(&mut self, _side: Side, _level: usize, _explored_count: usize) {}
+
+    /// The transitions of `state`, which is `level` transitions away from the start of `side`, are about to be applied.
+    fn node_expanded(&mut self, _side: Side, _state: &TState, _level: usize) {}
+
+    /// `state` was seen by both sides, the path will lead through it.
+    fn meeting_found(&mut self, _state: &TState) {}
+
+    /// Called once at the end with the number of transitions of the found path.
+    fn search_finished(&mut self, _result: Result<usize, SearchError>) {}
+}
+
+/// Ignores all events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl<TState> SearchObserver<TState> for NoopObserver {}
+
+/// Prints finished levels and the meeting node to stdout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutObserver;
+
+impl<TState> SearchObserver<TState> for StdoutObserver
+    where TState : Debug {
+
+    fn level_finished(&mut self, _side: Side, level: usize, explored_count: usize) {
+        // the first few levels are done in no time and would only clutter the output
+        if level > 3 {
+            println!("Finished level {level}, seen {explored_count} unique states");
+        }
+    }
+
+    fn meeting_found(&mut self, state: &TState) {
+        println!("We found a node on the way: {state:?}");
+    }
+}
+
+/// Forwards all events to the `log` crate.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogObserver;
+
+#[cfg(feature = "log")]
+impl<TState> SearchObserver<TState> for LogObserver
+    where TState : Debug {
+
+    fn level_finished(&mut self, side: Side, level: usize, explored_count: usize) {
+        log::debug!("{side:?} side finished level {level}, seen {explored_count} unique states");
+    }
+
+    fn node_expanded(&mut self, side: Side, state: &TState, level: usize) {
+        log::trace!("{side:?} side expanding {state:?} on level {level}");
+    }
+
+    fn meeting_found(&mut self, state: &TState) {
+        log::debug!("Both sides met at {state:?}");
+    }
+
+    fn search_finished(&mut self, result: Result<usize, SearchError>) {
+        match result {
+            Ok(path_length) => log::info!("Found path with {path_length} transitions"),
+            Err(error) => log::info!("Search failed: {error}"),
+        }
+    }
+}
+
+/// Emits all events as `tracing` events.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingObserver;
+
+#[cfg(feature = "tracing")]
+impl<TState> SearchObserver<TState> for TracingObserver
+    where TState : Debug {
+
+    fn level_finished(&mut self, side: Side, level: usize, explored_count: usize) {
+        tracing::debug!(?side, level, explored_count, "level finished");
+    }
+
+    fn node_expanded(&mut self, side: Side, state: &TState, level: usize) {
+        tracing::trace!(?side, ?state, level, "node expanded");
+    }
+
+    fn meeting_found(&mut self, state: &TState) {
+        tracing::debug!(?state, "meeting found");
+    }
+
+    fn search_finished(&mut self, result: Result<usize, SearchError>) {
+        match result {
+            Ok(path_length) => tracing::info!(path_length, "search finished"),
+            Err(error) => tracing::info!(%error, "search failed"),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Instant};
+
+use crate::SearchError;
+
+/// Configures a search started with [`crate::find_path_with`], everything is unlimited by default.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub strategy: Strategy,
+
+    /// Maximum number of transitions to explore from either side, found paths can be up to twice as long
+    pub max_depth: Option<usize>,
+
+    /// Maximum number of distinct states seen by both sides together
+    pub max_states: Option<usize>,
+
+    /// The search gives up once this point in time has passed
+    pub deadline: Option<Instant>,
+
+    /// The search stops soon after this was set to `true`, e.g. from another thread
+    pub cancellation: Option<Arc<AtomicBool>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Stop as soon as both sides have seen the same state, the path might be one transition longer than necessary
+    #[default]
+    FirstMeeting,
+
+    /// Expand complete levels so that the path is guaranteed to be as short as possible
+    Shortest,
+}
+
+/// Decides which side of the search is expanded next.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Switch sides after every new state, or after every level with [`SearchMode::Shortest`]
+    #[default]
+    Alternate,
+
+    /// Expand the side with fewer states waiting to be expanded, which pays off if one side branches far more than the other
+    SmallerFrontier,
+
+    /// Switch sides after every level, with [`SearchMode::FirstMeeting`] this behaves like [`SearchMode::Shortest`]
+    AlternateLevels,
+}
+
+/// Keeps track of the limits configured in [`SearchOptions`] while a search is running.
+pub(crate) struct Limits<'a> {
+    options: &'a SearchOptions,
+    checks: u32,
+}
+
+impl<'a> Limits<'a> {
+    /// Looking at the clock and the cancellation flag for every state would slow down the search noticeably
+    const CLOCK_CHECK_INTERVAL: u32 = 1024;
+
+    pub(crate) fn new(options: &'a SearchOptions) -> Limits<'a> {
+        Limits { options, checks: 0 }
+    }
+
+    pub(crate) fn check(&mut self, explored_states: usize) -> Result<(), SearchError> {
+        if self.options.max_states.is_some_and(|max_states| explored_states > max_states) {
+            return Err(SearchError::StateLimitReached);
+        }
+
+        let check_clock = self.checks.is_multiple_of(Self::CLOCK_CHECK_INTERVAL);
+        self.checks = self.checks.wrapping_add(1);
+
+        if !check_clock {
+            return Ok(());
+        }
+
+        if self.options.cancellation.as_ref().is_some_and(|cancelled| cancelled.load(Ordering::Relaxed)) {
+            return Err(SearchError::Cancelled);
+        }
+
+        if self.options.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(SearchError::DeadlineExceeded);
+        }
+
+        Ok(())
+    }
+}
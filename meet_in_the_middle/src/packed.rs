@@ -0,0 +1,185 @@
+use std::{collections::{hash_map::Entry, HashMap}, time::Instant};
+
+use crate::{layers::{self, Layers}, path::Solution, solver::{neighbor_states, transition_between}, PackedState, SearchError, SearchOptions, SearchStats, Side, SideStats};
+
+/// Finds a shortest way from `source` to `target` while only keeping packed states, see [`PackedState`].
+///
+/// For each state seen, only its packed form and the packed form of the state it was reached from are
+/// stored. States are unpacked to expand them, the transitions are looked up again once the way is known.
+/// The search goes level by level like [`crate::SearchMode::Shortest`], whatever `options.mode` says.
+pub fn find_path_packed<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : PackedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let started = Instant::now();
+    let max_level = options.max_depth.unwrap_or(usize::MAX);
+    let mut from_source = PackedLayers::new(source, Side::Source);
+    let mut from_target = PackedLayers::new(target, Side::Target);
+
+    let meeting = if source == target {
+        source.pack()
+    } else {
+        layers::meet(&mut from_source, &mut from_target, max_level, options)?
+    };
+
+    let stats = SearchStats {
+        from_source: from_source.stats(),
+        from_target: from_target.stats(),
+        meeting_levels: (from_source.level, from_target.level),
+        elapsed: started.elapsed(),
+    };
+
+    Ok(layers::join_ways(source.unpack(meeting), from_source.way_back(meeting)?, from_target.way_back(meeting)?).with_stats(stats))
+}
+
+/// The packed states seen by one side of [`find_path_packed`], each with the one it was reached from.
+struct PackedLayers<TState> where
+    TState : PackedState,
+{
+    /// The root is its own parent
+    parents: HashMap<TState::Packed, TState::Packed>,
+    frontier: Vec<TState::Packed>,
+    side: Side,
+    level: usize,
+
+    states_per_level: Vec<usize>,
+    peak_frontier_size: usize,
+
+    /// Used to unpack states
+    prototype: TState,
+}
+
+impl<TState, TTransition> PackedLayers<TState> where
+    TState : PackedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    fn new(root: &TState, side: Side) -> PackedLayers<TState> {
+        let packed = root.pack();
+
+        PackedLayers {
+            parents: HashMap::from([(packed, packed)]),
+            frontier: vec![packed],
+            side,
+            level: 0,
+            states_per_level: vec![1],
+            peak_frontier_size: 1,
+            prototype: root.clone(),
+        }
+    }
+
+    fn finish_level(&mut self, next_frontier: Vec<TState::Packed>) {
+        self.states_per_level.push(next_frontier.len());
+        self.peak_frontier_size = self.peak_frontier_size.max(next_frontier.len());
+        self.frontier = next_frontier;
+    }
+
+    /// The states leading from `packed` back to the root, each with the transition between it and the one before in forward direction.
+    fn way_back(&self, packed: TState::Packed) -> Result<Vec<(TState, TTransition)>, SearchError> {
+        let mut way = vec![];
+        let mut current = packed;
+
+        while let Some(&parent) = self.parents.get(&current) && parent != current {
+            let (state, parent_state) = (self.prototype.unpack(current), self.prototype.unpack(parent));
+
+            let transition = match self.side {
+                Side::Source => transition_between(&parent_state, &state)?,
+                Side::Target => transition_between(&state, &parent_state)?,
+            };
+
+            way.push((parent_state, transition));
+            current = parent;
+        }
+
+        Ok(way)
+    }
+
+    fn stats(&self) -> SideStats {
+        SideStats {
+            states_per_level: self.states_per_level.clone(),
+            peak_frontier_size: self.peak_frontier_size,
+        }
+    }
+}
+
+impl<TState, TTransition> Layers for PackedLayers<TState> where
+    TState : PackedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    type Meeting = TState::Packed;
+
+    fn level(&self) -> usize {
+        self.level
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.frontier.len()
+    }
+
+    fn seen_count(&self) -> usize {
+        self.parents.len()
+    }
+
+    fn expand(&mut self, other: &Self, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<Option<TState::Packed>, SearchError> {
+        let mut next_frontier = vec![];
+        self.level += 1;
+
+        for packed in std::mem::take(&mut self.frontier) {
+            let state = self.prototype.unpack(packed);
+            for neighbor in neighbor_states(&state, self.side)? {
+                let neighbor = neighbor.pack();
+
+                if let Entry::Vacant(entry) = self.parents.entry(neighbor) {
+                    entry.insert(packed);
+                    next_frontier.push(neighbor);
+
+                    if other.parents.contains_key(&neighbor) {
+                        self.finish_level(next_frontier);
+                        return Ok(Some(neighbor));
+                    }
+
+                    check_limits(self.parents.len())?;
+                }
+            }
+        }
+
+        self.finish_level(next_frontier);
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::state::test::{assert_matches_shortest_path, GraphNode};
+
+    use super::*;
+
+    #[test]
+    fn should_find_shortest_path() {
+        let nodes = GraphNode::grid(10);
+
+        let solution = find_path_packed(&nodes[0], &nodes[99], &SearchOptions::default()).unwrap();
+        assert_eq!(solution.len(), 18);
+        assert_eq!(solution.stats().meeting_levels, (9, 9));
+
+        let trivial = find_path_packed(&nodes[45], &nodes[45], &SearchOptions::default()).unwrap();
+        assert_eq!(trivial.states(), &[nodes[45].clone()]);
+    }
+
+    #[test]
+    fn should_respect_limits() {
+        let nodes = GraphNode::grid(10);
+
+        let shallow = SearchOptions { max_depth: Some(3), ..Default::default() };
+        assert_eq!(find_path_packed(&nodes[0], &nodes[99], &shallow), Err(SearchError::DepthLimitReached));
+
+        let expired = SearchOptions { deadline: Some(Instant::now()), ..Default::default() };
+        assert_eq!(find_path_packed(&nodes[0], &nodes[99], &expired), Err(SearchError::DeadlineExceeded));
+    }
+
+    #[test]
+    fn packed_path_should_match_shortest_path() {
+        assert_matches_shortest_path(1000, |source, target| find_path_packed(source, target, &SearchOptions::default()));
+    }
+}
@@ -0,0 +1,75 @@
+use std::{slice, time::Instant};
+
+use crate::{path::Solution, solver::Solver, visited::ShardedVisitedSet, NoopObserver, SearchError, SearchObserver, SearchOptions, State};
+
+/// Finds a shortest way from `source` to `target`, the states of each level are expanded on multiple threads.
+///
+/// A level is only split among threads as a whole, so `options.mode` is ignored. The solution is the same as
+/// [`crate::find_path_with`] finds with [`crate::SearchMode::Shortest`], no matter how many threads are used.
+/// Runs on the global rayon thread pool unless called within [`rayon::ThreadPool::install`].
+pub fn find_path_parallel<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition> + Send + Sync,
+    TTransition : Clone + Send + Sync,
+{
+    find_path_parallel_observed(source, target, options, &mut NoopObserver)
+}
+
+/// Like [`find_path_parallel`], notifying `observer` about the progress of the search from the calling thread.
+pub fn find_path_parallel_observed<TState, TTransition, TObserver>(source: &TState, target: &TState, options: &SearchOptions, observer: &mut TObserver) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition> + Send + Sync,
+    TTransition : Clone + Send + Sync,
+    TObserver : SearchObserver<TState>,
+{
+    let started = Instant::now();
+    let mut solver: Solver<_, _, ShardedVisitedSet<_>> = Solver::new(slice::from_ref(source), slice::from_ref(target), options.clone());
+
+    let node_on_path = match solver.common_root() {
+        Some(root) => Ok(root),
+        None => solver.run_shortest_parallel(observer),
+    };
+
+    let result = node_on_path.map(|node_on_path| {
+        let stats = solver.stats(&node_on_path, started);
+        solver.solution_through(&node_on_path).with_stats(stats)
+    });
+
+    observer.search_finished(result.as_ref().map(Solution::len).map_err(|error| *error));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{find_path_with, state::test::{for_random_pairs, GraphNode}, SearchMode, Strategy};
+
+    use super::*;
+
+    #[test]
+    fn should_stop_at_limits() {
+        let nodes = GraphNode::grid(10);
+
+        let shallow = SearchOptions { max_depth: Some(8), ..Default::default() };
+        assert_eq!(find_path_parallel(&nodes[0], &nodes[99], &shallow), Err(SearchError::DepthLimitReached));
+
+        let few_states = SearchOptions { max_states: Some(20), ..Default::default() };
+        assert_eq!(find_path_parallel(&nodes[0], &nodes[99], &few_states), Err(SearchError::StateLimitReached));
+
+        let deep_enough = SearchOptions { max_depth: Some(9), ..Default::default() };
+        assert_eq!(find_path_parallel(&nodes[0], &nodes[99], &deep_enough).map(|solution| solution.len()), Ok(18));
+    }
+
+    #[test]
+    fn should_find_same_path_as_single_thread() {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+
+        for_random_pairs(1000, |source, target| {
+            for strategy in [Strategy::Alternate, Strategy::SmallerFrontier] {
+                let options = SearchOptions { mode: SearchMode::Shortest, strategy, ..Default::default() };
+                let expected = find_path_with(source, target, &options).map(|solution| solution.transitions().to_vec());
+
+                let found = pool.install(|| find_path_parallel(source, target, &options));
+
+                assert_eq!(found.map(|solution| solution.transitions().to_vec()), expected, "{strategy:?} from {source:?} to {target:?}");
+            }
+        });
+    }
+}
@@ -1,3 +1,5 @@
+use crate::{SearchStats, WeightedState};
+
 #[derive(Debug, Clone, Eq)]
 pub enum Path<TTransition> {
     FromSource(Vec<TTransition>),
@@ -17,6 +19,96 @@ impl<TTransition> PartialEq for Path<TTransition>
     }
 }
 
+/// The states visited on the way from a source to a target, together with
+/// the transition leading from each state to the next one.
+#[derive(Debug, Clone)]
+pub struct Solution<TState, TTransition> {
+    states: Vec<TState>,
+    transitions: Vec<TTransition>,
+    stats: SearchStats,
+}
+
+impl<TState, TTransition> Solution<TState, TTransition> {
+    pub(crate) fn new(states: Vec<TState>, transitions: Vec<TTransition>) -> Solution<TState, TTransition> {
+        debug_assert_eq!(states.len(), transitions.len() + 1, "every transition needs a state before and after it");
+        Solution { states, transitions, stats: SearchStats::default() }
+    }
+
+    pub(crate) fn with_stats(self, stats: SearchStats) -> Solution<TState, TTransition> {
+        Solution { stats, ..self }
+    }
+
+    /// How much work it took to find this solution.
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// All states from source to target, both included.
+    pub fn states(&self) -> &[TState] {
+        &self.states
+    }
+
+    /// `transitions()[i]` leads from `states()[i]` to `states()[i + 1]`.
+    pub fn transitions(&self) -> &[TTransition] {
+        &self.transitions
+    }
+
+    pub fn source(&self) -> &TState {
+        self.states.first().expect("a solution always contains its source")
+    }
+
+    pub fn target(&self) -> &TState {
+        self.states.last().expect("a solution always contains its target")
+    }
+
+    /// Number of transitions needed to get from source to target.
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    /// Each transition together with the state it is applied to.
+    pub fn steps(&self) -> impl Iterator<Item = (&TState, &TTransition)> {
+        self.states.iter().zip(&self.transitions)
+    }
+
+    pub fn into_states(self) -> Vec<TState> {
+        self.states
+    }
+
+    pub fn to_path(&self) -> Path<TTransition>
+        where TTransition : Clone {
+
+        Path::FromSource(self.transitions.clone())
+    }
+}
+
+impl<TState, TTransition> Solution<TState, TTransition>
+    where TState : WeightedState<Transition = TTransition> {
+
+    /// Sum of the costs of all transitions taken, see [`WeightedState::cost`].
+    pub fn cost(&self) -> u64 {
+        self.steps().map(|(state, transition)| state.cost(transition)).sum()
+    }
+}
+
+/// Solutions are equal if they take the same way, regardless of how long it took to find them.
+impl<TState, TTransition> PartialEq for Solution<TState, TTransition>
+    where TState : PartialEq,
+    TTransition : PartialEq {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.states == other.states && self.transitions == other.transitions
+    }
+}
+
+impl<TState, TTransition> Eq for Solution<TState, TTransition>
+    where TState : Eq,
+    TTransition : Eq {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,197 @@
+use std::time::Instant;
+
+use crate::{layers::{self, Layers}, path::Solution, solver::{neighbor_states, neighbors}, visited::BitSet, RankedState, SearchError, SearchOptions, SearchStats, Side, SideStats};
+
+/// Finds a shortest way from `source` to `target`, storing two bytes per possible state and side, see [`RankedState`].
+///
+/// Each side keeps the depth of every state in an array indexed by rank and the states of its current level
+/// in a bitset, nothing else. Once the sides met, the way back is found by looking for neighbors one level
+/// closer to the start of each side, which only works since the depths come from complete levels, so
+/// `options.mode` has no effect. Fails with [`SearchError::DepthLimitReached`] if either side would need to go
+/// deeper than `u16::MAX - 1`.
+pub fn find_path_ranked<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : RankedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let started = Instant::now();
+    let max_level = options.max_depth.unwrap_or(usize::MAX).min(usize::from(UNSEEN - 1));
+    let mut from_source = RankedLayers::new(source, Side::Source);
+    let mut from_target = RankedLayers::new(target, Side::Target);
+
+    let meeting = if source == target {
+        source.index()
+    } else {
+        layers::meet(&mut from_source, &mut from_target, max_level, options)?
+    };
+
+    let stats = SearchStats {
+        from_source: from_source.stats(),
+        from_target: from_target.stats(),
+        meeting_levels: (from_source.level, from_target.level),
+        elapsed: started.elapsed(),
+    };
+
+    let meeting = source.unrank(meeting);
+    Ok(layers::join_ways(meeting.clone(), from_source.way_back(meeting.clone())?, from_target.way_back(meeting)?).with_stats(stats))
+}
+
+/// Depth of the states not seen yet
+const UNSEEN: u16 = u16::MAX;
+
+/// The states seen by one side of [`find_path_ranked`].
+struct RankedLayers<TState> {
+    /// Depth of each state by rank
+    depths: Vec<u16>,
+    frontier: BitSet,
+    frontier_size: usize,
+    seen_count: usize,
+    side: Side,
+    level: usize,
+
+    states_per_level: Vec<usize>,
+    peak_frontier_size: usize,
+
+    /// Where this side started, also used to unrank states
+    root: TState,
+}
+
+impl<TState, TTransition> RankedLayers<TState> where
+    TState : RankedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    fn new(root: &TState, side: Side) -> RankedLayers<TState> {
+        let state_count = root.state_count();
+        let mut depths = vec![UNSEEN; state_count];
+        let mut frontier = BitSet::new(state_count);
+
+        depths[root.index()] = 0;
+        frontier.insert(root.index());
+
+        RankedLayers {
+            depths,
+            frontier,
+            frontier_size: 1,
+            seen_count: 1,
+            side,
+            level: 0,
+            states_per_level: vec![1],
+            peak_frontier_size: 1,
+            root: root.clone(),
+        }
+    }
+
+    fn finish_level(&mut self, next_frontier: BitSet, next_frontier_size: usize) {
+        self.states_per_level.push(next_frontier_size);
+        self.peak_frontier_size = self.peak_frontier_size.max(next_frontier_size);
+        self.frontier = next_frontier;
+        self.frontier_size = next_frontier_size;
+    }
+
+    /// The states leading from `state` back to the root, each with the transition between it and the one before in forward direction.
+    fn way_back(&self, state: TState) -> Result<Vec<(TState, TTransition)>, SearchError> {
+        let mut way = vec![];
+        let mut current = state;
+
+        while current != self.root {
+            let closer_depth = self.depths[current.index()] - 1;
+            let is_closer = |state: &TState| self.depths[state.index()] == closer_depth;
+
+            let backwards = match self.side {
+                Side::Source => Side::Target,
+                Side::Target => Side::Source,
+            };
+
+            let (next, transition) = neighbors(&current, backwards)?.into_iter()
+                .find(|(neighbor, _)| is_closer(neighbor))
+                .ok_or(SearchError::NotReversible)?;
+
+            way.push((next.clone(), transition));
+            current = next;
+        }
+
+        Ok(way)
+    }
+
+    fn stats(&self) -> SideStats {
+        SideStats {
+            states_per_level: self.states_per_level.clone(),
+            peak_frontier_size: self.peak_frontier_size,
+        }
+    }
+}
+
+impl<TState, TTransition> Layers for RankedLayers<TState> where
+    TState : RankedState<Transition = TTransition>,
+    TTransition : Clone,
+{
+    /// The rank of the state
+    type Meeting = usize;
+
+    fn level(&self) -> usize {
+        self.level
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.frontier_size
+    }
+
+    fn seen_count(&self) -> usize {
+        self.seen_count
+    }
+
+    fn expand(&mut self, other: &Self, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<Option<usize>, SearchError> {
+        let mut next_frontier = BitSet::new(self.depths.len());
+        let mut next_frontier_size = 0;
+        self.level += 1;
+
+        let frontier = std::mem::take(&mut self.frontier);
+
+        for rank in frontier.iter() {
+            let state = self.root.unrank(rank);
+            for neighbor in neighbor_states(&state, self.side)? {
+                let neighbor = neighbor.index();
+
+                if self.depths[neighbor] == UNSEEN {
+                    self.depths[neighbor] = self.level as u16;
+                    self.seen_count += 1;
+                    next_frontier.insert(neighbor);
+                    next_frontier_size += 1;
+
+                    if other.depths[neighbor] != UNSEEN {
+                        self.finish_level(next_frontier, next_frontier_size);
+                        return Ok(Some(neighbor));
+                    }
+
+                    check_limits(self.seen_count)?;
+                }
+            }
+        }
+
+        self.finish_level(next_frontier, next_frontier_size);
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::test::{assert_matches_shortest_path, GraphNode};
+
+    use super::*;
+
+    #[test]
+    fn should_find_shortest_path() {
+        let nodes = GraphNode::grid(10);
+
+        let solution = find_path_ranked(&nodes[0], &nodes[99], &SearchOptions::default()).unwrap();
+        assert_eq!(solution.len(), 18);
+        assert_eq!(solution.stats().meeting_levels, (9, 9));
+
+        let shallow = SearchOptions { max_depth: Some(3), ..Default::default() };
+        assert_eq!(find_path_ranked(&nodes[0], &nodes[99], &shallow), Err(SearchError::DepthLimitReached));
+    }
+
+    #[test]
+    fn ranked_path_should_match_shortest_path() {
+        assert_matches_shortest_path(1000, |source, target| find_path_ranked(source, target, &SearchOptions::default()));
+    }
+}
@@ -1,106 +1,497 @@
-use std::collections::{HashSet, VecDeque};
+use std::{collections::{HashMap, VecDeque}, iter, slice, time::Instant};
 
-use crate::State;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-pub fn find_path<TState, TTransition>(source: &TState, target: &TState) -> impl IntoIterator<Item = TState> where
+#[cfg(feature = "serde")]
+use crate::checkpoint::{SavedDiscoverer, SavedSearch, SavedState};
+
+#[cfg(feature = "parallel")]
+use crate::visited::ShardedVisitedSet;
+use crate::{options::Limits, path::Solution, visited::{HashVisitedSet, VisitedSet}, NoopObserver, SearchError, SearchMode, SearchObserver, SearchOptions, SearchStats, Side, SideStats, State, Strategy};
+
+pub fn find_path<TState, TTransition>(source: &TState, target: &TState) -> Result<impl IntoIterator<Item = TState>, SearchError> where
     TState : State<Transition = TTransition>,
     TTransition : Clone,
 {
-    let mut nodes_between = find_nodes_on_path(source, target);
-    nodes_between.push_front(source.clone());
-    nodes_between.push_back(target.clone());
-    nodes_between
+    Ok(find_transitions(source, target)?.into_states())
 }
 
-pub fn find_nodes_on_path<TState, TTransition>(source: &TState, target: &TState) -> VecDeque<TState> where
+/// Finds a way from `source` to `target` and returns the visited states along
+/// with the transitions taken between them.
+pub fn find_transitions<TState, TTransition>(source: &TState, target: &TState) -> Result<Solution<TState, TTransition>, SearchError> where
     TState : State<Transition = TTransition>,
     TTransition : Clone,
 {
+    find_path_with(source, target, &SearchOptions::default())
+}
 
-    if source == target {
-        return VecDeque::new();
-    }
+/// Like [`find_transitions`], but guarantees that no shorter way from `source` to `target` exists.
+///
+/// Both sides are expanded a complete level at a time, which usually explores more states
+/// before the sides meet.
+pub fn find_shortest_path<TState, TTransition>(source: &TState, target: &TState) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+    find_path_with(source, target, &options)
+}
 
-    let mut neighbors_of_source = source.get_neighbors();
-    if neighbors_of_source.find(|n| n == target).is_some() {
-        return VecDeque::new();
-    }
+/// Finds a way from `source` to `target` within the limits given by `options`.
+pub fn find_path_with<TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    find_path_observed(source, target, options, &mut NoopObserver)
+}
 
-    // this might feel weird, but without handling this case as well we can
-    // endlessly recurse below. root cause / improvement might be to prevent
-    // Solver::run from returning such a result if it was found
-    let mut neighbors_of_target = target.get_neighbors();
-    if neighbors_of_target.find(|n| n == source).is_some() {
-        return VecDeque::new();
-    }
+/// Like [`find_path_with`], notifying `observer` about the progress of the search.
+pub fn find_path_observed<TState, TTransition, TObserver>(source: &TState, target: &TState, options: &SearchOptions, observer: &mut TObserver) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+    TObserver : SearchObserver<TState>,
+{
+    search::<_, _, _, HashVisitedSet<TState>>(slice::from_ref(source), slice::from_ref(target), options, observer)
+}
 
-    let mut solver = Solver::new(source.clone(), target.clone());
-    
-    if let Some(node_on_path) = solver.run() {
-        let mut solution = VecDeque::new();
-        solution.extend(find_nodes_on_path(source, &node_on_path));
-        solution.push_back(node_on_path.clone());
-        solution.extend(find_nodes_on_path(&node_on_path, target));
-        return solution;
-    }
+/// Finds a way from `source` to whichever of `targets` is reached first, see [`Solution::target`].
+///
+/// The search from the target side starts at all `targets` at once. With [`SearchMode::Shortest`],
+/// no other target can be reached in fewer transitions.
+pub fn find_path_to_any<TState, TTransition>(source: &TState, targets: &[TState], options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    search::<_, _, _, HashVisitedSet<TState>>(slice::from_ref(source), targets, options, &mut NoopObserver)
+}
 
-    VecDeque::new()
+/// Finds a way from whichever of `sources` to whichever of `targets` are closest to each other,
+/// see [`Solution::source`] and [`Solution::target`].
+///
+/// Both sides start at all of their states at once, e.g. to allow several equivalent start positions.
+pub fn find_path_between_any<TState, TTransition>(sources: &[TState], targets: &[TState], options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    search::<_, _, _, HashVisitedSet<TState>>(sources, targets, options, &mut NoopObserver)
 }
 
-pub struct Solver<TState, TTransition> where
+/// Finds a shortest way from `source` to any state for which `is_goal` returns `true`.
+///
+/// As the goal states are not known up front, there is no search from the target side, so this explores
+/// far more states than [`find_path_to_any`]. `options.mode` is ignored, the path is always a shortest one.
+pub fn find_path_to_goal<TState, TTransition>(source: &TState, is_goal: impl Fn(&TState) -> bool, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
     TState : State<Transition = TTransition>,
+    TTransition : Clone,
 {
-    source: TState,
-    target: TState
+    let started = Instant::now();
+    let mut limits = Limits::new(options);
+    let mut discoverer: Discoverer<_, _> = Discoverer::new(slice::from_ref(source), Side::Source, options.max_depth.unwrap_or(usize::MAX));
+
+    let goal = if is_goal(source) {
+        source.clone()
+    } else {
+        loop {
+            match discoverer.step(&mut NoopObserver)? {
+                Some((state, _)) if is_goal(&state) => break state,
+                Some(_) => limits.check(discoverer.explored_count())?,
+                None if discoverer.reached_max_level() => return Err(SearchError::DepthLimitReached),
+                None => return Err(SearchError::Unreachable),
+            }
+        }
+    };
+
+    let stats = SearchStats {
+        from_source: discoverer.stats(),
+        from_target: SideStats::default(),
+        meeting_levels: (discoverer.level_of(&goal).expect("goal was discovered"), 0),
+        elapsed: started.elapsed(),
+    };
+
+    Ok(join_at(&goal, discoverer.predecessors_of(&goal), iter::empty()).with_stats(stats))
 }
 
-impl<TState, TTransition> Solver<TState, TTransition>
+/// Like [`find_path_with`], keeping track of the states seen in a `TVisited`, e.g. a [`crate::FastVisitedSet`].
+pub fn find_path_using<TVisited, TState, TTransition>(source: &TState, target: &TState, options: &SearchOptions) -> Result<Solution<TState, TTransition>, SearchError> where
+    TVisited : VisitedSet<TState>,
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    search::<_, _, _, TVisited>(slice::from_ref(source), slice::from_ref(target), options, &mut NoopObserver)
+}
+
+fn search<TState, TTransition, TObserver, TVisited>(sources: &[TState], targets: &[TState], options: &SearchOptions, observer: &mut TObserver) -> Result<Solution<TState, TTransition>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+    TObserver : SearchObserver<TState>,
+    TVisited : VisitedSet<TState>,
+{
+    let started = Instant::now();
+    let mut solver: Solver<_, _, TVisited> = Solver::new(sources, targets, options.clone());
+
+    let node_on_path = match (solver.common_root(), options.mode, options.strategy) {
+        (Some(root), _, _) => Ok(root),
+        (None, SearchMode::Shortest, _) | (None, _, Strategy::AlternateLevels) => solver.run_shortest(observer),
+        (None, SearchMode::FirstMeeting, _) => solver.run(observer),
+    };
+
+    let result = node_on_path.map(|node_on_path| {
+        let stats = solver.stats(&node_on_path, started);
+        solver.solution_through(&node_on_path).with_stats(stats)
+    });
+
+    observer.search_finished(result.as_ref().map(Solution::len).map_err(|error| *error));
+    result
+}
+
+pub fn find_nodes_on_path<TState, TTransition>(source: &TState, target: &TState) -> Result<VecDeque<TState>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    let mut nodes = VecDeque::from(find_transitions(source, target)?.into_states());
+    nodes.pop_front();
+    nodes.pop_back();
+    Ok(nodes)
+}
+
+pub(crate) struct Solver<TState, TTransition, TVisited = HashVisitedSet<TState>> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+    TVisited : VisitedSet<TState>,
+{
+    from_source: Discoverer<TState, TTransition, TVisited>,
+    from_target: Discoverer<TState, TTransition, TVisited>,
+    options: SearchOptions,
+}
+
+impl<TState, TTransition, TVisited> Solver<TState, TTransition, TVisited>
     where TState : State<Transition = TTransition>,
     TTransition : Clone,
+    TVisited : VisitedSet<TState>,
 {
-    pub fn new(source: TState, target: TState) -> Solver<TState, TTransition> {
+    pub fn new(sources: &[TState], targets: &[TState], options: SearchOptions) -> Solver<TState, TTransition, TVisited> {
+        let max_level = options.max_depth.unwrap_or(usize::MAX);
+
         Solver {
-            source,
-            target,
+            from_source: Discoverer::new(sources, Side::Source, max_level),
+            from_target: Discoverer::new(targets, Side::Target, max_level),
+            options,
         }
     }
 
-    pub fn run(&mut self) -> Option<TState> {
-        let mut from_source = Discoverer::new(&self.source);
-        let mut from_target = Discoverer::new(&self.target);
+    /// Remembers all predecessors at the lowest level of each state instead of only the first one found.
+    pub(crate) fn keep_all_predecessors(&mut self) {
+        self.from_source.additional_predecessors = Some(HashMap::new());
+        self.from_target.additional_predecessors = Some(HashMap::new());
+    }
+
+    /// Counts the shortest ways to each state from the start of its side, must be called before anything was explored.
+    pub(crate) fn count_paths(&mut self) {
+        self.from_source.start_counting_paths();
+        self.from_target.start_counting_paths();
+    }
+
+    /// Number of shortest ways from the start of `side` to `state`, see [`Solver::count_paths`].
+    pub(crate) fn path_count(&self, side: Side, state: &TState) -> u128 {
+        let discoverer = match side {
+            Side::Source => &self.from_source,
+            Side::Target => &self.from_target,
+        };
+
+        discoverer.path_counts.as_ref().expect("paths are counted")[state]
+    }
+
+    /// All known predecessors of `state` on `side`, see [`Solver::keep_all_predecessors`].
+    pub(crate) fn all_predecessors_of(&self, side: Side, state: &TState) -> Vec<&Predecessor<TState, TTransition>> {
+        match side {
+            Side::Source => self.from_source.all_predecessors_of(state),
+            Side::Target => self.from_target.all_predecessors_of(state),
+        }
+    }
+
+    /// A state both sides start at, only meaningful before anything was explored.
+    pub fn common_root(&self) -> Option<TState> {
+        self.from_source.roots.iter().find(|&state| self.from_target.was_seen(state)).cloned()
+    }
+
+    /// Explores one state at a time from the side picked by the [`Strategy`] and returns the first node seen by both.
+    ///
+    /// Once either side has run out of states to explore without meeting the other,
+    /// everything reachable from it was seen and there is no way to get from source to target.
+    pub fn run(&mut self, observer: &mut impl SearchObserver<TState>) -> Result<TState, SearchError> {
+        let mut limits = Limits::new(&self.options);
+        let mut expand_source = false;
 
         loop {
-            if let Some(node_found_from_source) = Self::explore(&mut from_source, &from_target, 1) {
-                println!("We found a node on the way: {node_found_from_source:?}");
-                return Some(node_found_from_source);
+            if self.from_source.is_exhausted() || self.from_target.is_exhausted() {
+                return Err(SearchError::Unreachable);
             }
-            
-            if let Some(node_found_from_target) = Self::explore(&mut from_target, &from_source, 1) {
-                println!("We found a node on the way: {node_found_from_target:?}");
-                return Some(node_found_from_target);
+
+            expand_source = self.expand_source_next(expand_source)?;
+
+            let (discoverer, other_discoverer) = if expand_source {
+                (&mut self.from_source, &self.from_target)
+            } else {
+                (&mut self.from_target, &self.from_source)
+            };
+
+            if let Some(node_on_path) = Self::explore(discoverer, other_discoverer, 1, observer)? {
+                observer.meeting_found(&node_on_path);
+                return Ok(node_on_path);
             }
+
+            limits.check(self.from_source.explored_count() + self.from_target.explored_count())?;
         }
     }
 
-    fn explore(discoverer: &mut Discoverer<TState, TTransition>, other_discoverer: &Discoverer<TState, TTransition>, num_nodes: usize) -> Option<TState> {
-        for (new_state, _) in discoverer.take(num_nodes) {
+    /// Expands complete levels alternately from both sides and returns a node on a shortest path.
+    pub fn run_shortest(&mut self, observer: &mut impl SearchObserver<TState>) -> Result<TState, SearchError> {
+        let node_on_path = self.run_layered(observer)?.swap_remove(0);
+        observer.meeting_found(&node_on_path);
+        Ok(node_on_path)
+    }
+
+    /// Expands complete levels from the sides picked by the [`Strategy`] until they meet and returns all nodes where they met first.
+    ///
+    /// Before a level is expanded, the other side knows every state up to its current level and
+    /// nothing beyond it, so all nodes seen by both sides after the expansion lie on paths of the
+    /// same, shortest possible, length. Among those we keep the ones with the lowest combined level.
+    /// They are the nodes at the position of the just expanded level on all shortest paths, so each
+    /// shortest path leads through exactly one of them.
+    pub fn run_layered(&mut self, observer: &mut impl SearchObserver<TState>) -> Result<Vec<TState>, SearchError> {
+        self.run_layered_from(observer, false, |_, _| Ok(()))
+    }
+
+    /// Like [`Solver::run_layered`], given whether the source side was expanded last.
+    ///
+    /// `level_finished` is called with the solver and the side just expanded after each level which did not lead to a meeting.
+    pub(crate) fn run_layered_from(
+        &mut self,
+        observer: &mut impl SearchObserver<TState>,
+        expanded_source: bool,
+        level_finished: impl FnMut(&Self, bool) -> Result<(), SearchError>,
+    ) -> Result<Vec<TState>, SearchError> {
+        self.run_layers(observer, expanded_source, |discoverer, observer, check_limits| discoverer.explore_level(observer, check_limits), level_finished)
+    }
+
+    /// Like [`Solver::run_layered_from`], each level is expanded by `explore_level`, e.g. [`Discoverer::explore_level`].
+    fn run_layers<TObserver>(
+        &mut self,
+        observer: &mut TObserver,
+        expanded_source: bool,
+        mut explore_level: impl FnMut(&mut Discoverer<TState, TTransition, TVisited>, &mut TObserver, &mut dyn FnMut(usize) -> Result<(), SearchError>) -> Result<Vec<TState>, SearchError>,
+        mut level_finished: impl FnMut(&Self, bool) -> Result<(), SearchError>,
+    ) -> Result<Vec<TState>, SearchError> where
+        TObserver : SearchObserver<TState>,
+    {
+        let mut limits = Limits::new(&self.options);
+        let mut expand_source = expanded_source;
+
+        loop {
+            expand_source = self.expand_source_next(expand_source)?;
+
+            let (discoverer, other_discoverer) = if expand_source {
+                (&mut self.from_source, &self.from_target)
+            } else {
+                (&mut self.from_target, &self.from_source)
+            };
+
+            let other_explored_count = other_discoverer.explored_count();
+            let found = explore_level(discoverer, observer, &mut |explored_count| limits.check(explored_count + other_explored_count))?;
+
+            let meetings = found.into_iter()
+                .filter_map(|state| Self::meeting_at(discoverer, other_discoverer, state))
+                .collect();
+
+            if let Some(closest_meetings) = Self::closest(meetings) {
+                return Ok(closest_meetings);
+            }
+
+            if discoverer.is_exhausted() {
+                return Err(SearchError::Unreachable);
+            }
+
+            level_finished(self, expand_source)?;
+        }
+    }
+
+    /// The length of the paths through `state` if both sides have seen it.
+    fn meeting_at(discoverer: &Discoverer<TState, TTransition, TVisited>, other_discoverer: &Discoverer<TState, TTransition, TVisited>, state: TState) -> Option<(usize, TState)> {
+        let other_level = other_discoverer.level_of(&state)?;
+        let level = discoverer.level_of(&state).expect("was just explored");
+        Some((level + other_level, state))
+    }
+
+    /// The meetings on the shortest paths, `None` if there are no meetings at all.
+    fn closest(meetings: Vec<(usize, TState)>) -> Option<Vec<TState>> {
+        let path_length = meetings.iter().map(|(path_length, _)| *path_length).min()?;
+
+        Some(meetings.into_iter()
+            .filter(|(length, _)| *length == path_length)
+            .map(|(_, state)| state)
+            .collect())
+    }
+
+    /// Whether the source side is expanded next, given whether it was expanded last time.
+    ///
+    /// A side which reached the maximum level is skipped regardless of the [`Strategy`].
+    fn expand_source_next(&self, expanded_source: bool) -> Result<bool, SearchError> {
+        match (self.from_source.reached_max_level(), self.from_target.reached_max_level()) {
+            (true, true) => Err(SearchError::DepthLimitReached),
+            (true, false) => Ok(false),
+            (false, true) => Ok(true),
+            (false, false) => Ok(match self.options.strategy {
+                Strategy::SmallerFrontier => self.from_source.frontier_size() <= self.from_target.frontier_size(),
+                Strategy::Alternate | Strategy::AlternateLevels => !expanded_source,
+            }),
+        }
+    }
+
+    /// Summarizes the work done by both sides to meet at `node_on_path`.
+    pub fn stats(&self, node_on_path: &TState, started: Instant) -> SearchStats {
+        SearchStats {
+            from_source: self.from_source.stats(),
+            from_target: self.from_target.stats(),
+            meeting_levels: self.levels_of(node_on_path),
+            elapsed: started.elapsed(),
+        }
+    }
+
+    /// Number of transitions from the source and from the target to a node seen by both sides.
+    pub(crate) fn levels_of(&self, node_on_path: &TState) -> (usize, usize) {
+        let source_level = self.from_source.level_of(node_on_path).expect("node_on_path was seen by both sides");
+        let target_level = self.from_target.level_of(node_on_path).expect("node_on_path was seen by both sides");
+        (source_level, target_level)
+    }
+
+    /// Builds the complete solution through a node which was seen by both sides, see [`Solver::run`].
+    pub fn solution_through(&self, node_on_path: &TState) -> Solution<TState, TTransition> {
+        join_at(node_on_path, self.from_source.predecessors_of(node_on_path), self.from_target.predecessors_of(node_on_path))
+    }
+
+    fn explore(discoverer: &mut Discoverer<TState, TTransition, TVisited>, other_discoverer: &Discoverer<TState, TTransition, TVisited>, num_nodes: usize, observer: &mut impl SearchObserver<TState>) -> Result<Option<TState>, SearchError> {
+        for _ in 0..num_nodes {
+            let Some((new_state, _)) = discoverer.step(observer)? else {
+                return Ok(None);
+            };
+
             if other_discoverer.was_seen(&new_state) {
-                return Some(new_state);
+                return Ok(Some(new_state));
             }
         }
 
-        None
+        Ok(None)
     }
 }
 
-struct Discoverer<TState, TTransition> where
+#[cfg(feature = "parallel")]
+impl<TState, TTransition> Solver<TState, TTransition, ShardedVisitedSet<TState>>
+    where TState : State<Transition = TTransition> + Send + Sync,
+    TTransition : Clone + Send + Sync,
+{
+    /// Like [`Solver::run_shortest`], but each level is expanded on multiple threads, see [`Discoverer::explore_level_parallel`].
+    pub fn run_shortest_parallel(&mut self, observer: &mut impl SearchObserver<TState>) -> Result<TState, SearchError> {
+        let explore_level = |discoverer: &mut Discoverer<_, _, _>, observer: &mut _, check_limits: &mut dyn FnMut(usize) -> Result<(), SearchError>| {
+            discoverer.explore_level_parallel(observer, check_limits)
+        };
+
+        let node_on_path = self.run_layers(observer, false, explore_level, |_, _| Ok(()))?.swap_remove(0);
+        observer.meeting_found(&node_on_path);
+        Ok(node_on_path)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<TState, TTransition> Solver<TState, TTransition>
+    where TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    /// Everything both sides know, borrowed to be written out, must only be called between levels, see [`Solver::run_layered_from`].
+    pub(crate) fn to_saved(&self, expanded_source: bool) -> SavedSearch<&TState, &TTransition> {
+        SavedSearch {
+            from_source: self.from_source.to_saved(),
+            from_target: self.from_target.to_saved(),
+            expanded_source,
+        }
+    }
+
+    /// Continues where [`Solver::to_saved`] left off, returns the solver and whether the source side was expanded last.
+    pub(crate) fn from_saved(saved: SavedSearch<TState, TTransition>, options: SearchOptions) -> (Solver<TState, TTransition>, bool) {
+        let max_level = options.max_depth.unwrap_or(usize::MAX);
+
+        let solver = Solver {
+            from_source: Discoverer::from_saved(saved.from_source, Side::Source, max_level),
+            from_target: Discoverer::from_saved(saved.from_target, Side::Target, max_level),
+            options,
+        };
+
+        (solver, saved.expanded_source)
+    }
+}
+
+/// Builds the complete solution through `node_on_path` from the ways both sides took to reach it.
+pub(crate) fn join_at<'a, TState, TTransition>(
+    node_on_path: &TState,
+    from_source: impl Iterator<Item = &'a Predecessor<TState, TTransition>>,
+    from_target: impl Iterator<Item = &'a Predecessor<TState, TTransition>>,
+) -> Solution<TState, TTransition> where
+    TState : State<Transition = TTransition> + 'a,
+    TTransition : Clone + 'a,
+{
+    let mut states = vec![node_on_path.clone()];
+    let mut transitions = vec![];
+
+    for predecessor in from_source {
+        states.push(predecessor.state.clone());
+        transitions.push(predecessor.transition.clone());
+    }
+
+    states.reverse();
+    transitions.reverse();
+
+    for predecessor in from_target {
+        transitions.push(predecessor.transition.clone());
+        states.push(predecessor.state.clone());
+    }
+
+    Solution::new(states, transitions)
+}
+
+/// Breadth first search, as an iterator it yields every state reachable from where it started exactly once,
+/// together with the number of transitions needed to reach it, ordered by that depth.
+///
+/// The states seen are kept in a `TVisited`, see [`VisitedSet`].
+pub struct Discoverer<TState, TTransition, TVisited = HashVisitedSet<TState>> where
     TState : State<Transition = TTransition>,
     TTransition : Clone,
+    TVisited : VisitedSet<TState>,
 {
-    explored_states: HashSet<TState>,
+    visited: TVisited,
+
+    /// What is known about each state seen, at its position in `visited`
+    explored_states: Vec<Explored<TState, TTransition>>,
+
+    /// Where the search started, without duplicates
+    roots: Vec<TState>,
+
     states_to_explore: VecDeque<TState>,
     states_to_explore_next: VecDeque<TState>,
-    current_level: u8,
+    side: Side,
+    current_level: usize,
+    max_level: usize,
+
+    states_per_level: Vec<usize>,
+    peak_frontier_size: usize,
+
+    /// Further predecessors at the same level as the first one, `None` unless requested
+    additional_predecessors: Option<HashMap<TState, Vec<Predecessor<TState, TTransition>>>>,
+
+    /// Number of shortest ways to each state, `None` unless requested
+    path_counts: Option<HashMap<TState, u128>>,
+
+    /// Roots not yielded by the iterator yet, in reverse order
+    unreported_roots: Vec<TState>,
 
     current_state: CurrentState<TState, TTransition>
 }
@@ -109,73 +500,971 @@ impl<TState, TTransition> Discoverer<TState, TTransition> where
     TState : State<Transition = TTransition>,
     TTransition : Clone
 {
-    fn new(source: &TState) -> Discoverer<TState, TTransition> {
+    /// Searches from `root` without a depth limit.
+    pub fn starting_at(root: &TState) -> Discoverer<TState, TTransition> {
+        Discoverer::starting_at_any(slice::from_ref(root))
+    }
+
+    /// Searches from all `roots` at once, each of them has a depth of 0.
+    pub fn starting_at_any(roots: &[TState]) -> Discoverer<TState, TTransition> {
+        let mut discoverer = Discoverer::new(roots, Side::Source, usize::MAX);
+        discoverer.unreported_roots = discoverer.roots.iter().rev().cloned().collect();
+        discoverer
+    }
+}
+
+impl<TState, TTransition, TVisited> Discoverer<TState, TTransition, TVisited> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+    TVisited : VisitedSet<TState>,
+{
+    /// States which need more than `max_depth` transitions to be reached are not visited.
+    pub fn with_max_depth(self, max_depth: usize) -> Discoverer<TState, TTransition, TVisited> {
+        Discoverer { max_level: max_depth, ..self }
+    }
+
+    /// `states_per_depth()[d]` states have been found which need `d` transitions to be reached,
+    /// the counts are complete once the iterator is exhausted.
+    pub fn states_per_depth(&self) -> &[usize] {
+        &self.states_per_level
+    }
+
+    /// Starts at all `roots` at once, duplicates are ignored.
+    fn new(roots: &[TState], side: Side, max_level: usize) -> Discoverer<TState, TTransition, TVisited> {
+        let mut visited = TVisited::default();
+        let unique_roots: Vec<_> = roots.iter().filter(|&root| visited.insert(root).1).cloned().collect();
+
         Discoverer {
-            explored_states: HashSet::new(),
-            states_to_explore: VecDeque::new(),
+            visited,
+            explored_states: unique_roots.iter().map(|_| Explored { level: 0, predecessor: None }).collect(),
+            states_per_level: vec![unique_roots.len()],
+            peak_frontier_size: unique_roots.len(),
+            states_to_explore: unique_roots.iter().cloned().collect(),
+            roots: unique_roots,
             states_to_explore_next: VecDeque::new(),
+            side,
             current_level: 0,
-            current_state: CurrentState::new(source.clone())
+            max_level,
+            additional_predecessors: None,
+            path_counts: None,
+            unreported_roots: vec![],
+            current_state: CurrentState::empty(),
         }
     }
 
     fn add_for_later(&mut self, state: TState) {
         self.states_to_explore_next.push_back(state);
+        self.peak_frontier_size = self.peak_frontier_size.max(self.frontier_size());
+    }
+
+    /// Number of states found but not expanded yet.
+    fn frontier_size(&self) -> usize {
+        self.states_to_explore.len() + self.states_to_explore_next.len()
     }
 
     fn was_seen(&self, state: &TState) -> bool {
-        self.explored_states.contains(state)
+        self.visited.position(state).is_some()
+    }
+
+    fn explored_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// What is known about `state`, which must have been seen already.
+    fn explored(&self, state: &TState) -> &Explored<TState, TTransition> {
+        &self.explored_states[self.visited.position(state).expect("state was seen")]
+    }
+
+    fn stats(&self) -> SideStats {
+        SideStats {
+            states_per_level: self.states_per_level.clone(),
+            peak_frontier_size: self.peak_frontier_size,
+        }
+    }
+
+    /// Whether states of the current level would lead to states beyond the maximum level.
+    fn reached_max_level(&self) -> bool {
+        self.current_level >= self.max_level
+    }
+
+    /// Number of transitions needed to reach `state` from the source, if it was seen already.
+    fn level_of(&self, state: &TState) -> Option<usize> {
+        self.visited.position(state).map(|position| self.explored_states[position].level)
+    }
+
+    /// Records a neighbor of the state currently being expanded if it was not seen before.
+    fn discover(&mut self, (new_state, transition): (TState, TTransition)) -> Option<TState> {
+        let level = self.current_level + 1;
+        let state = self.current_state.state.as_ref().expect("neighbors are only known for an expanded state");
+
+        if let Some(path_counts) = &mut self.path_counts {
+            let ways_to_state = path_counts[state];
+
+            match self.visited.position(&new_state).map(|position| self.explored_states[position].level) {
+                None => { path_counts.insert(new_state.clone(), ways_to_state); },
+                Some(new_state_level) if new_state_level == level => {
+                    let ways_to_new_state = path_counts.get_mut(&new_state).expect("counted when discovered");
+                    *ways_to_new_state = ways_to_new_state.saturating_add(ways_to_state);
+                },
+                Some(_) => {},
+            }
+        }
+
+        match self.visited.insert(&new_state) {
+            (_, true) => {
+                let predecessor = Predecessor { state: state.clone(), transition };
+                self.record_new(new_state.clone(), predecessor);
+                Some(new_state)
+            },
+            (position, false) => {
+                if let Some(additional_predecessors) = &mut self.additional_predecessors && self.explored_states[position].level == level {
+                    let predecessor = Predecessor { state: state.clone(), transition };
+                    additional_predecessors.entry(new_state).or_default().push(predecessor);
+                }
+
+                None
+            },
+        }
+    }
+
+    /// Keeps what is known about a state just inserted into `visited` and queues it for the next level.
+    fn record_new(&mut self, new_state: TState, predecessor: Predecessor<TState, TTransition>) {
+        let level = self.current_level + 1;
+        self.explored_states.push(Explored { level, predecessor: Some(predecessor) });
+
+        if self.states_per_level.len() <= level {
+            self.states_per_level.push(0);
+        }
+        self.states_per_level[level] += 1;
+
+        self.add_for_later(new_state);
+    }
+
+    fn start_next_level(&mut self, observer: &mut impl SearchObserver<TState>) {
+        self.current_level += 1;
+        observer.level_finished(self.side, self.current_level, self.visited.len());
+
+        self.states_to_explore = std::mem::take(&mut self.states_to_explore_next);
+    }
+
+    fn expand(&mut self, state: TState, observer: &mut impl SearchObserver<TState>) -> Result<(), SearchError> {
+        observer.node_expanded(self.side, &state, self.current_level);
+        self.current_state = CurrentState::new(state, self.side)?;
+        Ok(())
+    }
+
+    /// Explores until the next new state was found and returns it with its level, `None` once there is nothing left to explore.
+    fn step(&mut self, observer: &mut impl SearchObserver<TState>) -> Result<Option<(TState, usize)>, SearchError> {
+        loop {
+            while self.current_state.remaining_neighbors.is_empty() {
+                if self.states_to_explore.is_empty() && !self.states_to_explore_next.is_empty() {
+                    self.start_next_level(observer);
+                }
+
+                if self.reached_max_level() {
+                    return Ok(None);
+                }
+
+                let Some(next) = self.states_to_explore.pop_front() else {
+                    return Ok(None);
+                };
+
+                self.expand(next, observer)?;
+            }
+
+            let next_neighbor = self.current_state.remaining_neighbors.pop().expect("would have returned in loop above");
+
+            if let Some(new_state) = self.discover(next_neighbor) {
+                return Ok(Some((new_state, self.current_level + 1)));
+            }
+        }
+    }
+
+    /// Expands all remaining states of the current level and returns the states found by doing so.
+    ///
+    /// `check_limits` is called with the number of explored states for each newly found state.
+    fn explore_level(&mut self, observer: &mut impl SearchObserver<TState>, mut check_limits: impl FnMut(usize) -> Result<(), SearchError>) -> Result<Vec<TState>, SearchError> {
+        let mut found = vec![];
+
+        loop {
+            self.discover_remaining(&mut found, &mut check_limits)?;
+
+            match self.states_to_explore.pop_front() {
+                Some(next) => self.expand(next, observer)?,
+                None => break,
+            }
+        }
+
+        self.start_next_level(observer);
+        Ok(found)
+    }
+
+    /// Discovers all remaining neighbors of the state currently being expanded and adds the new ones to `found`.
+    fn discover_remaining(&mut self, found: &mut Vec<TState>, check_limits: &mut impl FnMut(usize) -> Result<(), SearchError>) -> Result<(), SearchError> {
+        while let Some(neighbor) = self.current_state.remaining_neighbors.pop() {
+            if let Some(new_state) = self.discover(neighbor) {
+                found.push(new_state);
+                check_limits(self.visited.len())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether all states reachable from the source have been found already.
+    fn is_exhausted(&self) -> bool {
+        self.current_state.remaining_neighbors.is_empty()
+            && self.states_to_explore.is_empty()
+            && self.states_to_explore_next.is_empty()
+    }
+
+    fn start_counting_paths(&mut self) {
+        self.path_counts = Some(self.roots.iter().map(|root| (root.clone(), 1)).collect());
+    }
+
+    /// The first predecessor of `state` followed by the additional ones, if those are kept.
+    fn all_predecessors_of(&self, state: &TState) -> Vec<&Predecessor<TState, TTransition>> {
+        let first = self.explored(state).predecessor.as_ref();
+        let additional = self.additional_predecessors.as_ref().and_then(|additional| additional.get(state));
+
+        first.into_iter().chain(additional.into_iter().flatten()).collect()
+    }
+
+    /// Walks from `state` back to the source this discoverer started at, excluding `state` itself.
+    fn predecessors_of<'a>(&'a self, state: &'a TState) -> impl Iterator<Item = &'a Predecessor<TState, TTransition>> {
+        let mut current = state;
+
+        std::iter::from_fn(move || {
+            let predecessor = self.explored(current).predecessor.as_ref()?;
+            current = &predecessor.state;
+            Some(predecessor)
+        })
     }
 }
 
-impl<TState, TTransition> Iterator for Discoverer<TState, TTransition> where
+impl<TState, TTransition, TVisited> Iterator for Discoverer<TState, TTransition, TVisited> where
     TState : State<Transition = TTransition>,
-    TTransition : Clone
+    TTransition : Clone,
+    TVisited : VisitedSet<TState>,
 {
-    type Item = (TState, u8);
+    type Item = (TState, usize);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            while self.current_state.remaining_transitions.is_empty() {
-                let next = self.states_to_explore.pop_front().or_else(|| {
-                    self.current_level += 1;
-                    
-                    if self.current_level > 3 {
-                        println!("Finished level {}, seen {} unique states", self.current_level, self.explored_states.len());
-                    }
-        
-                    self.states_to_explore = std::mem::take(&mut self.states_to_explore_next);
-                    self.states_to_explore.pop_front()
-                });
+        match self.unreported_roots.pop() {
+            Some(root) => Some((root, 0)),
+            None => self.step(&mut NoopObserver).expect("neighbors from the source side are always known"),
+        }
+    }
+}
+
+/// Number of states expanded at once by [`Discoverer::explore_level_parallel`], bounding the neighbors held in memory
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_SIZE: usize = if cfg!(test) { 4 } else { 1 << 16 };
+
+/// Number of neighbors inserted into the visited set at once by [`Discoverer::explore_level_parallel`], bounding how
+/// many states it can find beyond a limit
+#[cfg(feature = "parallel")]
+const PARALLEL_BATCH_SIZE: usize = if cfg!(test) { 4 } else { 1 << 12 };
+
+#[cfg(feature = "parallel")]
+impl<TState, TTransition> Discoverer<TState, TTransition, ShardedVisitedSet<TState>> where
+    TState : State<Transition = TTransition> + Send + Sync,
+    TTransition : Clone + Send + Sync,
+{
+    /// Like [`Discoverer::explore_level`], but the states of the level are expanded on multiple threads, a chunk at a time.
+    ///
+    /// The neighbors of a chunk are found in parallel, each thread dropping those seen before right away. The
+    /// remaining ones are inserted into the shards of the visited set in parallel, see [`ShardedVisitedSet::insert_all`],
+    /// which numbers them in the same order as without threads, where the neighbors of a state are taken last to first. So the result does not depend on how the work was
+    /// split. Must not be called in the middle of a level, predecessors are only kept for the first way to each state.
+    ///
+    /// The neighbors are inserted [`PARALLEL_BATCH_SIZE`] at a time and `check_limits` is called for each new state
+    /// once its batch is in, so when a limit stops the search, fewer than [`PARALLEL_BATCH_SIZE`] states beyond
+    /// it can have been seen.
+    fn explore_level_parallel(&mut self, observer: &mut impl SearchObserver<TState>, mut check_limits: impl FnMut(usize) -> Result<(), SearchError>) -> Result<Vec<TState>, SearchError> {
+        debug_assert!(self.current_state.remaining_neighbors.is_empty(), "a level was partially explored");
+
+        let states: Vec<_> = std::mem::take(&mut self.states_to_explore).into();
+        let mut found = vec![];
+
+        for chunk in states.chunks(PARALLEL_CHUNK_SIZE) {
+            let (visited, side) = (&self.visited, self.side);
 
-                self.current_state = CurrentState::new(next?);
+            let unseen_neighbors: Vec<_> = chunk.par_iter().enumerate()
+                .map(|(index, state)| Ok(neighbors(state, side)?.into_iter().rev()
+                    .filter(|(neighbor, _)| visited.position(neighbor).is_none())
+                    .map(|(neighbor, transition)| (index, neighbor, transition))
+                    .collect::<Vec<_>>()))
+                .collect::<Result<Vec<_>, SearchError>>()?
+                .into_iter().flatten().collect();
+
+            for state in chunk {
+                observer.node_expanded(self.side, state, self.current_level);
             }
-            
-            let next_transition = self.current_state.remaining_transitions.pop().expect("would have returned in loop above");
-            let new_state = self.current_state.state.apply(&next_transition);
 
-            if self.explored_states.insert(new_state.clone()) {
-                self.add_for_later(new_state.clone());
-                return Some((new_state, self.current_level));
+            let mut unseen_neighbors = unseen_neighbors.into_iter();
+
+            loop {
+                let batch: Vec<_> = unseen_neighbors.by_ref().take(PARALLEL_BATCH_SIZE).collect();
+
+                if batch.is_empty() {
+                    break;
+                }
+
+                let candidates: Vec<_> = batch.iter().map(|(_, neighbor, _)| neighbor).collect();
+                let positions = self.visited.insert_all(&candidates);
+
+                for ((index, neighbor, transition), position) in batch.into_iter().zip(positions) {
+                    if position.is_some() {
+                        self.record_new(neighbor.clone(), Predecessor { state: chunk[index].clone(), transition });
+                        found.push(neighbor);
+                        check_limits(self.visited.len())?;
+                    }
+                }
             }
         }
+
+        self.start_next_level(observer);
+        Ok(found)
     }
 }
 
+#[cfg(feature = "serde")]
+impl<TState, TTransition> Discoverer<TState, TTransition> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    /// Each state seen in the order it was seen, with predecessors referring to earlier states by position.
+    fn to_saved(&self) -> SavedDiscoverer<&TState, &TTransition> {
+        debug_assert!(self.states_to_explore_next.is_empty() && self.current_state.remaining_neighbors.is_empty(), "a level was partially explored");
+
+        let mut states = vec![None; self.explored_states.len()];
+
+        for (state, position) in self.visited.iter() {
+            states[position] = Some(state);
+        }
+
+        let position_of = |state: &TState| self.visited.position(state).expect("only seen states are referred to");
+
+        SavedDiscoverer {
+            states: states.into_iter().zip(&self.explored_states)
+                .map(|(state, explored)| SavedState {
+                    state: state.expect("every position belongs to a state"),
+                    level: explored.level,
+                    predecessor: explored.predecessor.as_ref().map(|predecessor| (position_of(&predecessor.state), &predecessor.transition)),
+                })
+                .collect(),
+            frontier: self.states_to_explore.iter().map(position_of).collect(),
+            current_level: self.current_level,
+            states_per_level: self.states_per_level.clone(),
+            peak_frontier_size: self.peak_frontier_size,
+        }
+    }
+
+    /// Continues where [`Discoverer::to_saved`] left off, `saved` has to be consistent, see [`SavedDiscoverer::is_consistent`].
+    fn from_saved(saved: SavedDiscoverer<TState, TTransition>, side: Side, max_level: usize) -> Discoverer<TState, TTransition> {
+        let mut visited = HashVisitedSet::default();
+
+        for saved_state in &saved.states {
+            visited.insert(&saved_state.state);
+        }
+
+        let state_at = |position: usize| saved.states[position].state.clone();
+
+        Discoverer {
+            visited,
+            explored_states: saved.states.iter()
+                .map(|saved_state| Explored {
+                    level: saved_state.level,
+                    predecessor: saved_state.predecessor.as_ref().map(|(position, transition)| Predecessor { state: state_at(*position), transition: transition.clone() }),
+                })
+                .collect(),
+            roots: saved.states.iter().filter(|saved_state| saved_state.predecessor.is_none()).map(|saved_state| saved_state.state.clone()).collect(),
+            states_to_explore: saved.frontier.iter().map(|&position| state_at(position)).collect(),
+            states_to_explore_next: VecDeque::new(),
+            side,
+            current_level: saved.current_level,
+            max_level,
+            states_per_level: saved.states_per_level,
+            peak_frontier_size: saved.peak_frontier_size,
+            additional_predecessors: None,
+            path_counts: None,
+            unreported_roots: vec![],
+            current_state: CurrentState::empty(),
+        }
+    }
+}
+
+struct Explored<TState, TTransition> {
+    level: usize,
+
+    /// `None` only for the state the discoverer started at
+    predecessor: Option<Predecessor<TState, TTransition>>,
+}
+
+/// How a state was reached: on the source side by applying `transition` to `state`, on the
+/// target side the other way around, applying `transition` to the reached state leads to `state`.
+pub(crate) struct Predecessor<TState, TTransition> {
+    pub(crate) state: TState,
+    pub(crate) transition: TTransition,
+}
+
 struct CurrentState<TState, TTransition> where
     TState : State<Transition = TTransition>,
     TTransition : Clone,
 {
-    state: TState,
-    remaining_transitions: Vec<TTransition>
+    /// `None` before the first state was expanded
+    state: Option<TState>,
+    remaining_neighbors: Vec<(TState, TTransition)>
 }
 
 impl<TState, TTransition> CurrentState<TState, TTransition> where
     TState : State<Transition = TTransition>,
     TTransition : Clone
 {
-    fn new(state: TState) -> CurrentState<TState, TTransition> {
-        let transitions = state.get_possible_transitions().cloned().collect();
-        CurrentState { state, remaining_transitions: transitions }
+    fn new(state: TState, side: Side) -> Result<CurrentState<TState, TTransition>, SearchError> {
+        let neighbors = neighbors(&state, side)?;
+        Ok(CurrentState { state: Some(state), remaining_neighbors: neighbors })
+    }
+
+    fn empty() -> CurrentState<TState, TTransition> {
+        CurrentState { state: None, remaining_neighbors: vec![] }
+    }
+}
+
+/// States one transition away from `state` in the direction `side` searches in, each with the
+/// transition between them in forward direction, see [`Predecessor`].
+pub(crate) fn neighbors<TState, TTransition>(state: &TState, side: Side) -> Result<Vec<(TState, TTransition)>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    match side {
+        Side::Source => Ok(state.get_possible_transitions().map(|t| (state.apply(t), t.clone())).collect()),
+        Side::Target => state.get_predecessors().collect(),
+    }
+}
+
+/// Like [`neighbors`], without the transitions.
+pub(crate) fn neighbor_states<TState, TTransition>(state: &TState, side: Side) -> Result<Vec<TState>, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    match side {
+        Side::Source => Ok(state.get_neighbors().collect()),
+        Side::Target => state.get_predecessors().map(|predecessor| predecessor.map(|(state, _)| state)).collect(),
+    }
+}
+
+/// A transition leading from `from` to `to`, only looked up once `to` is known to lie on the way.
+pub(crate) fn transition_between<TState, TTransition>(from: &TState, to: &TState) -> Result<TTransition, SearchError> where
+    TState : State<Transition = TTransition>,
+    TTransition : Clone,
+{
+    from.get_possible_transitions()
+        .find(|&transition| from.apply(transition) == *to)
+        .cloned()
+        .ok_or(SearchError::NotReversible)
+}
+#[cfg(test)]
+mod tests {
+    use std::{collections::hash_map::Entry, sync::{atomic::AtomicBool, Arc}, time::Instant};
+
+    use rand::Rng;
+
+    use crate::{all_shortest_paths, count_shortest_paths, distance, distance_indexed, find_cheapest_path, find_path_external, find_path_packed, find_path_ranked, state::test::{assert_valid, for_random_graphs, for_random_pairs, random_node, GraphNode}, ExternalStorage, FastVisitedSet, IndexedState, IndexedVisitedSet, PackedState, PrecomputedGoal, RankedState, WeightedState};
+
+    use super::*;
+
+    #[test]
+    fn should_find_path_within_component() {
+        let nodes = GraphNode::undirected(6, &[(0, 1), (1, 2), (2, 3), (4, 5)]);
+
+        let solution = find_transitions(&nodes[0], &nodes[3]).unwrap();
+
+        assert_eq!(solution.transitions(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn should_report_unreachable_target() {
+        let nodes = GraphNode::undirected(6, &[(0, 1), (1, 2), (2, 3), (4, 5)]);
+
+        assert_eq!(find_transitions(&nodes[0], &nodes[5]), Err(SearchError::Unreachable));
+        assert_eq!(find_transitions(&nodes[5], &nodes[0]), Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn should_follow_directed_edges() {
+        // applying the transitions of the target would lead straight back to the source
+        let nodes = GraphNode::directed(3, &[(0, 1), (1, 2), (2, 0)]);
+
+        assert_eq!(find_transitions(&nodes[0], &nodes[2]).unwrap().transitions(), &[1, 2]);
+        assert_eq!(find_transitions(&nodes[2], &nodes[1]).unwrap().transitions(), &[0, 1]);
+        assert_eq!(find_shortest_path(&nodes[1], &nodes[0]).unwrap().transitions(), &[2, 0]);
+    }
+
+    #[test]
+    fn should_report_unreachable_isolated_node() {
+        let nodes = GraphNode::undirected(3, &[(0, 1)]);
+
+        assert_eq!(find_transitions(&nodes[0], &nodes[2]), Err(SearchError::Unreachable));
+        assert_eq!(find_transitions(&nodes[2], &nodes[0]), Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn should_stop_at_max_depth() {
+        let nodes = line_graph(10);
+
+        for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+            let too_shallow = SearchOptions { mode, max_depth: Some(4), ..Default::default() };
+            assert_eq!(find_path_with(&nodes[0], &nodes[9], &too_shallow), Err(SearchError::DepthLimitReached));
+
+            let deep_enough = SearchOptions { mode, max_depth: Some(5), ..Default::default() };
+            assert_eq!(find_path_with(&nodes[0], &nodes[9], &deep_enough).map(|s| s.len()), Ok(9));
+        }
+    }
+
+    #[test]
+    fn should_stop_at_max_states() {
+        let nodes = line_graph(50);
+
+        for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+            let options = SearchOptions { mode, max_states: Some(10), ..Default::default() };
+            assert_eq!(find_path_with(&nodes[0], &nodes[49], &options), Err(SearchError::StateLimitReached));
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_level_should_stop_within_a_batch_of_max_states() {
+        let spokes: Vec<_> = (1..30).map(|i| (0, i)).collect();
+        let nodes = GraphNode::undirected(30, &spokes);
+        let options = SearchOptions { max_states: Some(10), ..Default::default() };
+        let mut limits = Limits::new(&options);
+        let mut discoverer: Discoverer<_, _, ShardedVisitedSet<_>> = Discoverer::new(slice::from_ref(&nodes[0]), Side::Source, usize::MAX);
+
+        let result = discoverer.explore_level_parallel(&mut NoopObserver, |explored_count| limits.check(explored_count));
+
+        assert_eq!(result.map(|found| found.len()), Err(SearchError::StateLimitReached));
+        assert!(discoverer.explored_count() < 10 + PARALLEL_BATCH_SIZE, "{} states seen", discoverer.explored_count());
+    }
+
+    #[test]
+    fn should_stop_at_deadline() {
+        let nodes = line_graph(50);
+
+        for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+            let options = SearchOptions { mode, deadline: Some(Instant::now()), ..Default::default() };
+            assert_eq!(find_path_with(&nodes[0], &nodes[49], &options), Err(SearchError::DeadlineExceeded));
+        }
+    }
+
+    #[test]
+    fn should_stop_when_cancelled() {
+        let nodes = line_graph(50);
+
+        for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+            let options = SearchOptions { mode, cancellation: Some(Arc::new(AtomicBool::new(true))), ..Default::default() };
+            assert_eq!(find_path_with(&nodes[0], &nodes[49], &options), Err(SearchError::Cancelled));
+        }
+    }
+
+    #[test]
+    fn should_notify_observer() {
+        #[derive(Default)]
+        struct RecordingObserver {
+            finished_levels: Vec<(Side, usize)>,
+            expanded: usize,
+            meetings: Vec<usize>,
+            results: Vec<Result<usize, SearchError>>,
+        }
+
+        impl SearchObserver<GraphNode> for RecordingObserver {
+            fn level_finished(&mut self, side: Side, level: usize, _explored_count: usize) {
+                self.finished_levels.push((side, level));
+            }
+
+            fn node_expanded(&mut self, _side: Side, _state: &GraphNode, _level: usize) {
+                self.expanded += 1;
+            }
+
+            fn meeting_found(&mut self, state: &GraphNode) {
+                self.meetings.push(state.index);
+            }
+
+            fn search_finished(&mut self, result: Result<usize, SearchError>) {
+                self.results.push(result);
+            }
+        }
+
+        let nodes = line_graph(7);
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+        let mut observer = RecordingObserver::default();
+
+        find_path_observed(&nodes[0], &nodes[6], &options, &mut observer).unwrap();
+
+        assert_eq!(observer.finished_levels, vec![(Side::Source, 1), (Side::Target, 1), (Side::Source, 2), (Side::Target, 2), (Side::Source, 3), (Side::Target, 3)]);
+        assert_eq!(observer.expanded, 6);
+        assert_eq!(observer.meetings, vec![3]);
+        assert_eq!(observer.results, vec![Ok(6)]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_report_stats() {
+        let nodes = line_graph(7);
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+        let solution = find_path_with(&nodes[0], &nodes[6], &options).unwrap();
+        let stats = solution.stats();
+
+        assert_eq!(stats.meeting_levels, (3, 3));
+        assert_eq!(stats.from_source.states_per_level, vec![1, 1, 1, 1]);
+        assert_eq!(stats.from_target.states_per_level, vec![1, 1, 1, 1]);
+        assert_eq!(stats.from_source.peak_frontier_size, 1);
+        assert_eq!(stats.explored_count(), 8);
+    }
+
+    #[test]
+    fn discoverer_should_visit_states_by_depth() {
+        let nodes = GraphNode::grid(3);
+
+        let visited: Vec<_> = Discoverer::starting_at(&nodes[0]).map(|(node, depth)| (node.index, depth)).collect();
+
+        assert_eq!(visited[0], (0, 0));
+        assert_eq!(visited.len(), 9);
+        assert!(visited.iter().all(|&(index, depth)| index % 3 + index / 3 == depth));
+        assert!(visited.is_sorted_by_key(|&(_, depth)| depth));
+    }
+
+    #[test]
+    fn discoverer_should_count_states_per_depth() {
+        let nodes = GraphNode::grid(3);
+
+        let mut discoverer = Discoverer::starting_at(&nodes[0]);
+        assert_eq!(discoverer.by_ref().count(), 9);
+        assert_eq!(discoverer.states_per_depth(), &[1, 2, 3, 2, 1]);
+
+        let mut limited = Discoverer::starting_at_any(&[nodes[0].clone(), nodes[8].clone()]).with_max_depth(1);
+        assert_eq!(limited.by_ref().count(), 6);
+        assert_eq!(limited.states_per_depth(), &[2, 4]);
+    }
+
+    #[test]
+    fn shortest_path_should_match_breadth_first_search() {
+        for_random_pairs(1000, |source, target| {
+            let expected_length = breadth_first_distance(source, target);
+
+            match find_shortest_path(source, target) {
+                Ok(solution) => {
+                    assert_eq!(Some(solution.len()), expected_length, "from {source:?} to {target:?}");
+                    assert_valid(&solution, source, target);
+                },
+                Err(error) => {
+                    assert_eq!(error, SearchError::Unreachable);
+                    assert_eq!(expected_length, None, "from {source:?} to {target:?}");
+                },
+            }
+        });
+    }
+
+    #[test]
+    fn every_strategy_should_match_breadth_first_search() {
+        for_random_pairs(1000, |source, target| {
+            let expected_length = breadth_first_distance(source, target);
+
+            for strategy in [Strategy::Alternate, Strategy::SmallerFrontier, Strategy::AlternateLevels] {
+                for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+                    let options = SearchOptions { mode, strategy, ..Default::default() };
+
+                    match (find_path_with(source, target, &options), expected_length) {
+                        (Ok(solution), Some(expected_length)) => {
+                            let is_shortest = mode == SearchMode::Shortest || strategy == Strategy::AlternateLevels;
+                            let allowed_lengths = if is_shortest { expected_length..=expected_length } else { expected_length..=expected_length + 1 };
+
+                            assert!(allowed_lengths.contains(&solution.len()), "{strategy:?} {mode:?} from {source:?} to {target:?}");
+                            assert_valid(&solution, source, target);
+                        },
+                        (result, expected_length) => {
+                            assert_eq!(result.map(|solution| solution.len()), Err(SearchError::Unreachable));
+                            assert_eq!(expected_length, None, "{strategy:?} {mode:?} from {source:?} to {target:?}");
+                        },
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn every_visited_set_should_find_same_path() {
+        for_random_pairs(1000, |source, target| {
+            for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+                let options = SearchOptions { mode, ..Default::default() };
+                let transitions = |result: Result<Solution<_, _>, _>| result.map(|solution| solution.transitions().to_vec());
+                let expected = transitions(find_path_with(source, target, &options));
+
+                assert_eq!(transitions(find_path_using::<FastVisitedSet<_>, _, _>(source, target, &options)), expected);
+                assert_eq!(transitions(find_path_using::<IndexedVisitedSet, _, _>(source, target, &options)), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn smaller_frontier_should_expand_narrow_side() {
+        // every node branches into ten, but has a single predecessor
+        let edges: Vec<_> = (1..10).map(|i| (0, i))
+            .chain((1..100).flat_map(|i| (0..10).map(move |digit| (i, 10 * i + digit))))
+            .collect();
+        let nodes = GraphNode::directed(1000, &edges);
+
+        for mode in [SearchMode::FirstMeeting, SearchMode::Shortest] {
+            let explored_count = |strategy| {
+                let options = SearchOptions { mode, strategy, ..Default::default() };
+                let solution = find_path_with(&nodes[0], &nodes[100], &options).unwrap();
+                assert_eq!(solution.len(), 3);
+                solution.stats().explored_count()
+            };
+
+            assert!(explored_count(Strategy::SmallerFrontier) < explored_count(Strategy::AlternateLevels), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn should_reach_closest_target() {
+        let nodes = line_graph(10);
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+        let solution = find_path_to_any(&nodes[0], &[nodes[7].clone(), nodes[3].clone()], &options).unwrap();
+
+        assert_eq!(solution.target(), &nodes[3]);
+        assert_eq!(solution.transitions(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn should_handle_source_among_targets() {
+        let nodes = line_graph(3);
+
+        let solution = find_path_to_any(&nodes[1], &nodes, &SearchOptions::default()).unwrap();
+
+        assert_eq!(solution.states(), &[nodes[1].clone()]);
+        assert_eq!(find_path_to_any(&nodes[1], &[], &SearchOptions::default()), Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn should_reach_goal() {
+        let nodes = line_graph(10);
+
+        let solution = find_path_to_goal(&nodes[0], |node: &GraphNode| node.index % 4 == 3, &SearchOptions::default()).unwrap();
+        assert_eq!(solution.transitions(), &[1, 2, 3]);
+
+        let limited = SearchOptions { max_depth: Some(2), ..Default::default() };
+        assert_eq!(find_path_to_goal(&nodes[0], |node: &GraphNode| node.index == 9, &limited), Err(SearchError::DepthLimitReached));
+        assert_eq!(find_path_to_goal(&nodes[0], |node: &GraphNode| node.index > 9, &SearchOptions::default()), Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn closest_target_should_match_breadth_first_search() {
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+        for_random_graphs(1000, 40, 2, |nodes, rng| {
+            let source = random_node(rng, nodes);
+            let targets: Vec<_> = (0..rng.random_range(0..4)).map(|_| random_node(rng, nodes).clone()).collect();
+
+            let expected_length = targets.iter().filter_map(|target| breadth_first_distance(source, target)).min();
+
+            let to_any = find_path_to_any(source, &targets, &options);
+            let to_goal = find_path_to_goal(source, |state| targets.contains(state), &options);
+
+            for result in [to_any, to_goal] {
+                match result {
+                    Ok(solution) => {
+                        assert_eq!(Some(solution.len()), expected_length, "from {source:?} to {targets:?}");
+                        assert!(targets.contains(solution.target()));
+                        assert_valid(&solution, source, solution.target());
+                    },
+                    Err(error) => {
+                        assert_eq!(error, SearchError::Unreachable);
+                        assert_eq!(expected_length, None, "from {source:?} to {targets:?}");
+                    },
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn should_start_at_closest_source() {
+        let nodes = line_graph(10);
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+        let solution = find_path_between_any(&[nodes[0].clone(), nodes[8].clone()], &[nodes[5].clone()], &options).unwrap();
+
+        assert_eq!(solution.source(), &nodes[8]);
+        assert_eq!(solution.transitions(), &[7, 6, 5]);
+    }
+
+    #[test]
+    fn closest_pair_should_match_breadth_first_search() {
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+        for_random_graphs(1000, 40, 2, |nodes, rng| {
+            let mut pick = || -> Vec<_> { (0..rng.random_range(0..4)).map(|_| random_node(rng, nodes).clone()).collect() };
+            let sources = pick();
+            let targets = pick();
+
+            let expected_length = sources.iter()
+                .flat_map(|source| targets.iter().filter_map(|target| breadth_first_distance(source, target)))
+                .min();
+
+            match find_path_between_any(&sources, &targets, &options) {
+                Ok(solution) => {
+                    assert_eq!(Some(solution.len()), expected_length, "from {sources:?} to {targets:?}");
+                    assert!(sources.contains(solution.source()));
+                    assert!(targets.contains(solution.target()));
+                    assert_valid(&solution, solution.source(), solution.target());
+                },
+                Err(error) => {
+                    assert_eq!(error, SearchError::Unreachable);
+                    assert_eq!(expected_length, None, "from {sources:?} to {targets:?}");
+                },
+            }
+        });
+    }
+
+    #[test]
+    fn every_search_should_agree_on_directed_graph() {
+        let nodes = GraphNode::directed(7, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3), (0, 4), (5, 1), (6, 0)]);
+
+        for source in &nodes {
+            for target in &nodes {
+                let expected = breadth_first_distance(source, target).ok_or(SearchError::Unreachable);
+
+                for (search, length) in length_by_search(source, target) {
+                    assert_eq!(length, expected, "{search} from {source:?} to {target:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_search_should_report_transitions_which_are_not_reversible() {
+        // a cycle, the dead ends make the source side wider so every strategy expands the target side
+        let edges: Vec<_> = (0..6).map(|i| (i, (i + 1) % 6)).chain([(0, 6), (0, 7)]).collect();
+        let nodes: Vec<_> = GraphNode::directed(8, &edges).into_iter().map(OneWay).collect();
+
+        for (search, length) in length_by_search(&nodes[0], &nodes[3]) {
+            assert_eq!(length, Err(SearchError::NotReversible), "{search}");
+        }
+    }
+
+    /// Length of the way from `source` to `target` found by every search which is meant to find a shortest one.
+    fn length_by_search<TState>(source: &TState, target: &TState) -> Vec<(&'static str, Result<usize, SearchError>)> where
+        TState : WeightedState<Transition = usize> + PackedState + RankedState + Send + Sync,
+    {
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+        let with_strategy = |strategy| SearchOptions { strategy, ..options.clone() };
+        let length = |result: Result<Solution<TState, usize>, SearchError>| result.map(|solution| solution.len());
+        let (sources, targets) = (slice::from_ref(source), slice::from_ref(target));
+
+        #[cfg_attr(not(feature = "parallel"), allow(unused_mut))]
+        let mut lengths = vec![
+            ("find_shortest_path", length(find_shortest_path(source, target))),
+            ("find_path_with", length(find_path_with(source, target, &options))),
+            ("smaller frontier", length(find_path_with(source, target, &with_strategy(Strategy::SmallerFrontier)))),
+            ("alternate levels", length(find_path_with(source, target, &with_strategy(Strategy::AlternateLevels)))),
+            ("find_path_using", length(find_path_using::<IndexedVisitedSet, _, _>(source, target, &options))),
+            ("find_path_to_any", length(find_path_to_any(source, targets, &options))),
+            ("find_path_between_any", length(find_path_between_any(sources, targets, &options))),
+            ("find_cheapest_path", length(find_cheapest_path(source, target))),
+            ("find_path_packed", length(find_path_packed(source, target, &options))),
+            ("find_path_ranked", length(find_path_ranked(source, target, &options))),
+            ("find_path_external", length(find_path_external(source, target, &ExternalStorage::default(), &options))),
+            ("find_path", length(PrecomputedGoal::new(target, 1).and_then(|goal| goal.find_path(source, &options)))),
+            ("find_path_deepening", length(PrecomputedGoal::new(target, 1).and_then(|goal| goal.find_path_deepening(source, &options)))),
+            ("distance", distance(source, target, &options).and_then(|distance| distance.ok_or(SearchError::Unreachable))),
+            ("distance_indexed", distance_indexed(source, target, &options).and_then(|distance| distance.ok_or(SearchError::Unreachable))),
+            ("all_shortest_paths", all_shortest_paths(source, target, &options).map(|mut solutions| solutions.next().expect("a way was found").len())),
+            ("count_shortest_paths", count_shortest_paths(source, target, &options).map(|(_, length)| length)),
+        ];
+
+        #[cfg(feature = "parallel")]
+        lengths.push(("find_path_parallel", length(crate::find_path_parallel(source, target, &options))));
+
+        lengths
+    }
+
+    /// A [`GraphNode`] relying on the default [`State::get_predecessors`], which needs every transition to be reversible.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct OneWay(GraphNode);
+
+    impl State for OneWay {
+        type Transition = usize;
+
+        fn apply(&self, change: &usize) -> OneWay {
+            OneWay(self.0.apply(change))
+        }
+
+        fn get_possible_transitions(&self) -> impl Iterator<Item = &usize> {
+            self.0.get_possible_transitions()
+        }
+    }
+
+    impl WeightedState for OneWay {
+        fn cost(&self, transition: &usize) -> u64 {
+            self.0.cost(transition)
+        }
+    }
+
+    impl IndexedState for OneWay {
+        fn index(&self) -> usize {
+            self.0.index()
+        }
+    }
+
+    impl PackedState for OneWay {
+        type Packed = u64;
+
+        fn pack(&self) -> u64 {
+            self.0.pack()
+        }
+
+        fn unpack(&self, packed: u64) -> OneWay {
+            OneWay(self.0.unpack(packed))
+        }
+    }
+
+    impl RankedState for OneWay {
+        fn state_count(&self) -> usize {
+            self.0.state_count()
+        }
+
+        fn unrank(&self, rank: usize) -> OneWay {
+            OneWay(self.0.unrank(rank))
+        }
+    }
+
+    fn line_graph(node_count: usize) -> Vec<GraphNode> {
+        let edges: Vec<_> = (1..node_count).map(|i| (i - 1, i)).collect();
+        GraphNode::undirected(node_count, &edges)
+    }
+
+    /// Plain unidirectional breadth first search as reference
+    fn breadth_first_distance<TState: State>(source: &TState, target: &TState) -> Option<usize> {
+        let mut distances = HashMap::from([(source.clone(), 0)]);
+        let mut queue = VecDeque::from([source.clone()]);
+
+        while let Some(state) = queue.pop_front() {
+            let distance = distances[&state];
+
+            if state == *target {
+                return Some(distance);
+            }
+
+            for neighbor in state.get_neighbors() {
+                if let Entry::Vacant(entry) = distances.entry(neighbor.clone()) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        None
+    }
+}
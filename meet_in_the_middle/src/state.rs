@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use crate::SearchError;
+
 pub trait State : Hash + Eq + Debug + Clone {
     type Transition;
 
@@ -9,6 +11,332 @@ pub trait State : Hash + Eq + Debug + Clone {
     fn get_possible_transitions(&self) -> impl Iterator<Item = &Self::Transition>;
 
     fn get_neighbors(&self) -> impl Iterator<Item = Self> {
-        self.get_possible_transitions().into_iter().map(|t| self.apply(&t))
+        self.get_possible_transitions().map(|t| self.apply(t))
+    }
+
+    /// All states leading to `self` with a single transition, each with the transition leading to `self`.
+    ///
+    /// The search from the target walks these backwards. By default every transition is assumed to be
+    /// reversible, so the neighbors are the predecessors and the transition is looked up among those of
+    /// each of them. A neighbor without one leading back to `self` is reported as
+    /// [`SearchError::NotReversible`] instead of being left out. Override this for directed graphs, or if
+    /// the inverse of a transition is known.
+    fn get_predecessors(&self) -> impl Iterator<Item = Result<(Self, Self::Transition), SearchError>>
+        where Self::Transition : Clone {
+
+        self.get_neighbors().map(|predecessor| {
+            let transition = predecessor.get_possible_transitions()
+                .find(|&t| predecessor.apply(t) == *self)
+                .ok_or(SearchError::NotReversible)?
+                .clone();
+
+            Ok((predecessor, transition))
+        })
+    }
+}
+
+/// A [`State`] whose transitions differ in how expensive they are, see [`crate::find_cheapest_path`].
+///
+/// The search from the target uses the costs of the transitions given by [`State::get_predecessors`].
+pub trait WeightedState : State {
+    fn cost(&self, transition: &Self::Transition) -> u64;
+}
+
+/// A [`State`] which can be stored as a single number, see [`crate::find_path_packed`].
+pub trait PackedState : State {
+    /// Either `u64` or `u128`
+    type Packed : PackedKey;
+
+    fn pack(&self) -> Self::Packed;
+
+    /// The state which was packed into `packed`. Whatever is not part of the packed number, e.g. the size
+    /// of a board, is taken from `self`, which can be any state of the same search.
+    fn unpack(&self, packed: Self::Packed) -> Self;
+}
+
+/// Numbers states can be packed into, see [`PackedState`].
+pub trait PackedKey : Copy + Ord + Hash + Debug {
+    /// Number of bytes the number takes on disk, see [`crate::find_path_external`].
+    const BYTES: usize;
+
+    /// Writes the number to `bytes`, which are exactly [`PackedKey::BYTES`] long.
+    fn write_bytes(self, bytes: &mut [u8]);
+
+    /// The number written by [`PackedKey::write_bytes`].
+    fn read_bytes(bytes: &[u8]) -> Self;
+}
+
+impl PackedKey for u64 {
+    const BYTES: usize = 8;
+
+    fn write_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Self {
+        u64::from_le_bytes(bytes.try_into().expect("a u64 takes 8 bytes"))
+    }
+}
+
+impl PackedKey for u128 {
+    const BYTES: usize = 16;
+
+    fn write_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Self {
+        u128::from_le_bytes(bytes.try_into().expect("a u128 takes 16 bytes"))
+    }
+}
+
+/// A [`State`] which can be turned into a number unique to it, see [`crate::IndexedVisitedSet`] and [`RankedState`].
+pub trait IndexedState : State {
+    fn index(&self) -> usize;
+}
+
+/// An [`IndexedState`] whose index, its rank, is below a known bound and can be turned back into the state,
+/// see [`crate::find_path_ranked`].
+///
+/// Like with [`PackedState`], `self` provides whatever is needed beyond the rank, e.g. the size of a board.
+pub trait RankedState : IndexedState {
+    /// Number of states there are, every rank is below it.
+    fn state_count(&self) -> usize;
+
+    /// The state whose rank is `rank`.
+    fn unrank(&self, rank: usize) -> Self;
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{collections::HashMap, hash::Hasher, sync::Arc};
+
+    use rand::{rngs, Rng, SeedableRng};
+
+    use crate::{find_path_with, path::Solution, Heuristic, SearchError, SearchMode, SearchOptions};
+
+    use super::*;
+
+    /// Asserts that `solution` leads from `source` to `target` and that each of its transitions leads to the next state.
+    pub(crate) fn assert_valid<TState: State>(solution: &Solution<TState, TState::Transition>, source: &TState, target: &TState) {
+        assert_eq!(solution.source(), source);
+        assert_eq!(solution.target(), target);
+
+        for (i, (from, transition)) in solution.steps().enumerate() {
+            assert_eq!(from.apply(transition), solution.states()[i + 1]);
+        }
+    }
+
+    /// Asserts that `result` is a valid way as long as the one [`find_path_with`] finds in [`SearchMode::Shortest`],
+    /// or fails the same way.
+    pub(crate) fn assert_shortest(result: Result<Solution<GraphNode, usize>, SearchError>, source: &GraphNode, target: &GraphNode) {
+        let options = SearchOptions { mode: SearchMode::Shortest, ..Default::default() };
+
+        match (result, find_path_with(source, target, &options)) {
+            (Ok(solution), Ok(expected)) => {
+                assert_eq!(solution.len(), expected.len(), "from {source:?} to {target:?}");
+                assert_valid(&solution, source, target);
+            },
+            (result, expected) => assert_eq!(result.map(|solution| solution.len()), expected.map(|solution| solution.len()), "from {source:?} to {target:?}"),
+        }
+    }
+
+    /// Calls [`assert_shortest`] with what `find` returns for each pair of [`for_random_pairs`].
+    pub(crate) fn assert_matches_shortest_path(graph_count: usize, mut find: impl FnMut(&GraphNode, &GraphNode) -> Result<Solution<GraphNode, usize>, SearchError>) {
+        for_random_pairs(graph_count, |source, target| assert_shortest(find(source, target), source, target));
+    }
+
+    /// Calls `check` with a random source and target in each of `graph_count` random graphs with fewer than 40 nodes,
+    /// see [`for_random_graphs`].
+    pub(crate) fn for_random_pairs(graph_count: usize, mut check: impl FnMut(&GraphNode, &GraphNode)) {
+        for_random_graphs(graph_count, 40, 2, |nodes, rng| {
+            let source = random_node(rng, nodes);
+            let target = random_node(rng, nodes);
+            check(source, target);
+        });
+    }
+
+    /// Calls `check` with the nodes of each of `graph_count` [`GraphNode::random`] graphs, alternately undirected and
+    /// directed, and the generator they came from to pick nodes with. The graphs are the same on every run.
+    pub(crate) fn for_random_graphs(graph_count: usize, max_node_count: usize, edges_per_node: usize, mut check: impl FnMut(&[GraphNode], &mut rngs::StdRng)) {
+        let mut rng = rngs::StdRng::seed_from_u64(42);
+
+        for directed in [false, true].into_iter().cycle().take(graph_count) {
+            let nodes = GraphNode::random(&mut rng, directed, max_node_count, edges_per_node);
+            check(&nodes, &mut rng);
+        }
+    }
+
+    pub(crate) fn random_node<'a, TRng: Rng>(rng: &mut TRng, nodes: &'a [GraphNode]) -> &'a GraphNode {
+        &nodes[rng.random_range(0..nodes.len())]
+    }
+
+    /// Node of an explicitly given graph, a transition is the index of the node to move to.
+    #[derive(Clone)]
+    pub(crate) struct GraphNode {
+        edges: Arc<Vec<Vec<usize>>>,
+        reverse_edges: Arc<Vec<Vec<usize>>>,
+
+        /// Cost of moving between two nodes, 1 if missing
+        costs: Arc<HashMap<(usize, usize), u64>>,
+        pub(crate) index: usize,
+    }
+
+    impl GraphNode {
+        /// All nodes of a graph with `node_count` nodes where each pair in `edges` can be traversed in both directions.
+        pub(crate) fn undirected(node_count: usize, edges: &[(usize, usize)]) -> Vec<GraphNode> {
+            let both_directions: Vec<_> = edges.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect();
+            GraphNode::directed(node_count, &both_directions)
+        }
+
+        /// Square grid with `size * size` nodes, numbered row by row, where each node is connected to its horizontal and vertical neighbors.
+        pub(crate) fn grid(size: usize) -> Vec<GraphNode> {
+            let edges: Vec<_> = (0..size * size)
+                .flat_map(|i| {
+                    let right = (i % size + 1 < size).then_some((i, i + 1));
+                    let down = (i + size < size * size).then_some((i, i + size));
+                    right.into_iter().chain(down)
+                })
+                .collect();
+
+            GraphNode::undirected(size * size, &edges)
+        }
+
+        /// A graph with fewer than `max_node_count` nodes and fewer than `edges_per_node` random edges per node,
+        /// some nodes usually can't reach each other.
+        pub(crate) fn random<TRng: Rng>(rng: &mut TRng, directed: bool, max_node_count: usize, edges_per_node: usize) -> Vec<GraphNode> {
+            let node_count = rng.random_range(1..max_node_count);
+            let edge_count = rng.random_range(0..node_count * edges_per_node);
+
+            let edges: Vec<_> = (0..edge_count)
+                .map(|_| (rng.random_range(0..node_count), rng.random_range(0..node_count)))
+                .collect();
+
+            if directed {
+                GraphNode::directed(node_count, &edges)
+            } else {
+                GraphNode::undirected(node_count, &edges)
+            }
+        }
+
+        /// All nodes of a graph with `node_count` nodes where each pair `(a, b)` in `edges` can only be traversed from `a` to `b`.
+        pub(crate) fn directed(node_count: usize, edges: &[(usize, usize)]) -> Vec<GraphNode> {
+            let mut adjacency = vec![vec![]; node_count];
+            let mut reverse_adjacency = vec![vec![]; node_count];
+
+            for &(a, b) in edges {
+                adjacency[a].push(b);
+                reverse_adjacency[b].push(a);
+            }
+
+            let adjacency = Arc::new(adjacency);
+            let reverse_adjacency = Arc::new(reverse_adjacency);
+            let costs = Arc::new(HashMap::new());
+
+            (0..node_count)
+                .map(|index| GraphNode { edges: adjacency.clone(), reverse_edges: reverse_adjacency.clone(), costs: costs.clone(), index })
+                .collect()
+        }
+
+        /// Like [`GraphNode::undirected`], but moving along `(a, b, cost)` costs `cost` in either direction.
+        ///
+        /// If a pair of nodes is connected more than once, the cheapest connection counts.
+        pub(crate) fn weighted(node_count: usize, edges: &[(usize, usize, u64)]) -> Vec<GraphNode> {
+            let mut costs = HashMap::new();
+
+            for &(a, b, cost) in edges {
+                for key in [(a, b), (b, a)] {
+                    costs.entry(key).and_modify(|existing: &mut u64| *existing = cost.min(*existing)).or_insert(cost);
+                }
+            }
+
+            let pairs: Vec<_> = edges.iter().map(|&(a, b, _)| (a, b)).collect();
+            let costs = Arc::new(costs);
+
+            GraphNode::undirected(node_count, &pairs).into_iter()
+                .map(|node| GraphNode { costs: costs.clone(), ..node })
+                .collect()
+        }
+    }
+
+    impl PartialEq for GraphNode {
+        fn eq(&self, other: &Self) -> bool {
+            self.index == other.index
+        }
+    }
+
+    impl Eq for GraphNode {}
+
+    impl Hash for GraphNode {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.index.hash(state);
+        }
+    }
+
+    impl Debug for GraphNode {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "GraphNode({})", self.index)
+        }
+    }
+
+    impl State for GraphNode {
+        type Transition = usize;
+
+        fn apply(&self, change: &Self::Transition) -> Self {
+            GraphNode { index: *change, ..self.clone() }
+        }
+
+        fn get_possible_transitions(&self) -> impl Iterator<Item = &Self::Transition> {
+            self.edges[self.index].iter()
+        }
+
+        fn get_predecessors(&self) -> impl Iterator<Item = Result<(Self, Self::Transition), SearchError>> {
+            self.reverse_edges[self.index].iter().map(|&index| Ok((GraphNode { index, ..self.clone() }, self.index)))
+        }
+    }
+
+    impl WeightedState for GraphNode {
+        fn cost(&self, transition: &Self::Transition) -> u64 {
+            self.costs.get(&(self.index, *transition)).copied().unwrap_or(1)
+        }
+    }
+
+    impl IndexedState for GraphNode {
+        fn index(&self) -> usize {
+            self.index
+        }
+    }
+
+    impl PackedState for GraphNode {
+        type Packed = u64;
+
+        fn pack(&self) -> u64 {
+            self.index as u64
+        }
+
+        fn unpack(&self, packed: u64) -> GraphNode {
+            GraphNode { index: packed as usize, ..self.clone() }
+        }
+    }
+
+    impl RankedState for GraphNode {
+        fn state_count(&self) -> usize {
+            self.edges.len()
+        }
+
+        fn unrank(&self, rank: usize) -> GraphNode {
+            GraphNode { index: rank, ..self.clone() }
+        }
+    }
+
+    /// Manhattan distance within a [`GraphNode::grid`] of the given size.
+    pub(crate) struct GridDistance(pub(crate) usize);
+
+    impl Heuristic<GraphNode> for GridDistance {
+        fn estimate(&self, from: &GraphNode, to: &GraphNode) -> u64 {
+            let (from_x, from_y) = (from.index % self.0, from.index / self.0);
+            let (to_x, to_y) = (to.index % self.0, to.index / self.0);
+            (from_x.abs_diff(to_x) + from_y.abs_diff(to_y)) as u64
+        }
     }
 }
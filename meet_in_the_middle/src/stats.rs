@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+/// Numbers describing how much work a search needed, see [`crate::Solution::stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchStats {
+    pub from_source: SideStats,
+    pub from_target: SideStats,
+
+    /// Number of transitions from source and from target to the state where both sides met
+    pub meeting_levels: (usize, usize),
+
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    /// Number of distinct states seen by both sides together.
+    pub fn explored_count(&self) -> usize {
+        self.from_source.explored_count() + self.from_target.explored_count()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SideStats {
+    /// `states_per_level[i]` states were found `i` transitions away from where this side started
+    pub states_per_level: Vec<usize>,
+
+    /// Maximum number of states waiting to be expanded at the same time
+    pub peak_frontier_size: usize,
+}
+
+impl SideStats {
+    /// Number of distinct states seen by this side, which is also the peak size of its set of seen states.
+    pub fn explored_count(&self) -> usize {
+        self.states_per_level.iter().sum()
+    }
+
+    /// The branching factor a uniform tree would need to contain the same number of states
+    /// within the same number of levels, i.e. `b` such that `b + b^2 + ... + b^depth` equals
+    /// the number of states found beyond the start.
+    pub fn effective_branching_factor(&self) -> f64 {
+        let depth = self.states_per_level.iter().rposition(|&count| count > 0).unwrap_or(0);
+        let found = (self.explored_count() - self.states_per_level.first().copied().unwrap_or(0)) as f64;
+
+        if depth == 0 {
+            return 0.0;
+        }
+
+        let states_within_depth = |branching_factor: f64| (1..=depth).map(|level| branching_factor.powi(level as i32)).sum::<f64>();
+
+        // the sum grows monotonically with the branching factor, which can not be larger than the number of states
+        let (mut low, mut high) = (0.0, found);
+        for _ in 0..100 {
+            let middle = (low + high) / 2.0;
+
+            if states_within_depth(middle) < found {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        (low + high) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_branching_factor_of_binary_tree() {
+        let stats = SideStats { states_per_level: vec![1, 2, 4, 8], peak_frontier_size: 8 };
+
+        assert!((stats.effective_branching_factor() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn effective_branching_factor_of_line() {
+        let stats = SideStats { states_per_level: vec![1, 1, 1, 1, 1], peak_frontier_size: 1 };
+
+        assert!((stats.effective_branching_factor() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn effective_branching_factor_without_transitions() {
+        let stats = SideStats { states_per_level: vec![1], peak_frontier_size: 0 };
+
+        assert_eq!(stats.effective_branching_factor(), 0.0);
+    }
+}
@@ -0,0 +1,370 @@
+use std::{collections::{hash_map::{Entry, RandomState}, HashMap}, hash::{BuildHasher, BuildHasherDefault, Hash, Hasher}};
+
+use crate::IndexedState;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Remembers which states a search has seen and numbers them in the order they were first seen.
+///
+/// Everything else the search knows about a state is kept in a list at that position. Pick the
+/// implementation for a search with [`crate::find_path_using`], [`HashVisitedSet`] is used by default.
+pub trait VisitedSet<TState> : Default {
+    /// Adds `state` unless it was seen before and returns its position, along with whether it is new.
+    fn insert(&mut self, state: &TState) -> (usize, bool);
+
+    /// The position of `state` if it was seen before.
+    fn position(&self, state: &TState) -> Option<usize>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Keeps the states in a [`HashMap`] whose hash function is built by `S`, see [`FastVisitedSet`].
+pub struct HashVisitedSet<TState, S = RandomState> {
+    positions: HashMap<TState, usize, S>,
+}
+
+impl<TState, S> Default for HashVisitedSet<TState, S> where
+    S : Default,
+{
+    fn default() -> Self {
+        HashVisitedSet { positions: HashMap::default() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<TState, S> HashVisitedSet<TState, S> {
+    /// Each state seen with its position, in no particular order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&TState, usize)> {
+        self.positions.iter().map(|(state, &position)| (state, position))
+    }
+}
+
+impl<TState, S> VisitedSet<TState> for HashVisitedSet<TState, S> where
+    TState : Hash + Eq + Clone,
+    S : BuildHasher + Default,
+{
+    fn insert(&mut self, state: &TState) -> (usize, bool) {
+        let len = self.positions.len();
+
+        match self.positions.entry(state.clone()) {
+            Entry::Vacant(entry) => (*entry.insert(len), true),
+            Entry::Occupied(entry) => (*entry.get(), false),
+        }
+    }
+
+    fn position(&self, state: &TState) -> Option<usize> {
+        self.positions.get(state).copied()
+    }
+
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+}
+
+/// A [`HashVisitedSet`] with the much faster [`FastHasher`].
+pub type FastVisitedSet<TState> = HashVisitedSet<TState, BuildHasherDefault<FastHasher>>;
+
+/// Multiplicative hash function as used within rustc, far faster than the default of [`HashMap`].
+///
+/// It offers no protection against inputs crafted to collide, which is no concern for states the search generates itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FastHasher {
+    hash: u64,
+}
+
+impl FastHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+impl Hasher for FastHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut words = bytes.chunks_exact(8);
+
+        for word in &mut words {
+            self.add(u64::from_le_bytes(word.try_into().expect("chunks have 8 bytes")));
+        }
+
+        for &byte in words.remainder() {
+            self.add(byte.into());
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(i.into());
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add(i.into());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(i.into());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Remembers the [`IndexedState::index`] of each state seen in a bitset, so states are not hashed and this
+/// set holds none of them. A search still keeps the state each one was reached from, see
+/// [`crate::distance_indexed`] for a bitset without positions.
+///
+/// The bits take an eighth of a byte per index up to the highest one seen. The positions are kept apart from
+/// them in a hash map by index, which takes two `usize`s and some spare capacity per state seen.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedVisitedSet {
+    seen: BitSet,
+    positions: HashMap<usize, usize, BuildHasherDefault<FastHasher>>,
+}
+
+impl<TState> VisitedSet<TState> for IndexedVisitedSet where
+    TState : IndexedState,
+{
+    fn insert(&mut self, state: &TState) -> (usize, bool) {
+        let index = state.index();
+
+        if self.seen.insert(index) {
+            let position = self.positions.len();
+            self.positions.insert(index, position);
+            (position, true)
+        } else {
+            (self.positions[&index], false)
+        }
+    }
+
+    fn position(&self, state: &TState) -> Option<usize> {
+        let index = state.index();
+
+        if self.seen.contains(index) {
+            self.positions.get(&index).copied()
+        } else {
+            None
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+}
+
+/// One bit per number, it grows up to the highest number inserted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// Room for the numbers below `len` without growing.
+    pub(crate) fn new(len: usize) -> BitSet {
+        BitSet { words: vec![0; len.div_ceil(64)] }
+    }
+
+    /// Returns whether `index` was not in the set before.
+    pub(crate) fn insert(&mut self, index: usize) -> bool {
+        let (word, bit) = (index / 64, 1 << (index % 64));
+
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        let is_new = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        is_new
+    }
+
+    pub(crate) fn contains(&self, index: usize) -> bool {
+        self.words.get(index / 64).is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    /// The numbers in the set, in ascending order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(word_index, &word)| {
+            let mut remaining = word;
+
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(word_index * 64 + bit)
+            })
+        })
+    }
+}
+
+/// Number of bits of a hash picking the shard of a [`ShardedVisitedSet`]
+#[cfg(feature = "parallel")]
+const SHARD_BITS: u32 = 6;
+
+/// Splits the states among several [`HashMap`]s by their hash, so a batch of states can be inserted on
+/// multiple threads, see [`ShardedVisitedSet::insert_all`].
+#[cfg(feature = "parallel")]
+pub(crate) struct ShardedVisitedSet<TState> {
+    shards: Vec<HashMap<TState, usize, BuildHasherDefault<FastHasher>>>,
+    len: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl<TState> Default for ShardedVisitedSet<TState> {
+    fn default() -> Self {
+        ShardedVisitedSet { shards: (0..1 << SHARD_BITS).map(|_| HashMap::default()).collect(), len: 0 }
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<TState> ShardedVisitedSet<TState> where
+    TState : Hash + Eq + Clone + Send + Sync,
+{
+    /// The shards use the low bits of the hash of a state, so the shard is picked by the high bits of it scrambled once more.
+    fn shard_of(state: &TState) -> usize {
+        let mut hasher = FastHasher::default();
+        state.hash(&mut hasher);
+        (hasher.finish().wrapping_mul(FastHasher::SEED) >> (64 - SHARD_BITS)) as usize
+    }
+
+    /// Like calling [`VisitedSet::insert`] for each of `states` in order, returns the positions of the new ones.
+    ///
+    /// Each shard inserts its states on a thread of its own and in the given order, so the first of equal states
+    /// counts as new. Positions are only handed out afterwards, in the given order as well.
+    pub(crate) fn insert_all(&mut self, states: &[&TState]) -> Vec<Option<usize>> {
+        let mut by_shard = vec![vec![]; self.shards.len()];
+
+        for (index, shard) in states.par_iter().map(|state| Self::shard_of(state)).collect::<Vec<_>>().into_iter().enumerate() {
+            by_shard[shard].push(index);
+        }
+
+        let new_by_shard: Vec<Vec<usize>> = self.shards.par_iter_mut().zip(&by_shard)
+            .map(|(shard, indexes)| indexes.iter().copied()
+                .filter(|&index| match shard.entry(states[index].clone()) {
+                    Entry::Vacant(entry) => { entry.insert(usize::MAX); true },
+                    Entry::Occupied(_) => false,
+                })
+                .collect())
+            .collect();
+
+        // mark the new states first, then number them in order
+        let mut positions = vec![None; states.len()];
+
+        for &index in new_by_shard.iter().flatten() {
+            positions[index] = Some(usize::MAX);
+        }
+
+        for position in positions.iter_mut().flatten() {
+            *position = self.len;
+            self.len += 1;
+        }
+
+        self.shards.par_iter_mut().zip(&new_by_shard).for_each(|(shard, indexes)| {
+            for &index in indexes {
+                *shard.get_mut(states[index]).expect("was inserted above") = positions[index].expect("is new");
+            }
+        });
+
+        positions
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<TState> VisitedSet<TState> for ShardedVisitedSet<TState> where
+    TState : Hash + Eq + Clone + Send + Sync,
+{
+    fn insert(&mut self, state: &TState) -> (usize, bool) {
+        let len = self.len;
+
+        match self.shards[Self::shard_of(state)].entry(state.clone()) {
+            Entry::Vacant(entry) => {
+                self.len += 1;
+                (*entry.insert(len), true)
+            },
+            Entry::Occupied(entry) => (*entry.get(), false),
+        }
+    }
+
+    fn position(&self, state: &TState) -> Option<usize> {
+        self.shards[Self::shard_of(state)].get(state).copied()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::test::GraphNode;
+
+    use super::*;
+
+    fn should_number_states_in_order<TVisited: VisitedSet<GraphNode>>() {
+        let nodes = GraphNode::undirected(10, &[]);
+        let mut visited = TVisited::default();
+
+        assert_eq!(visited.insert(&nodes[7]), (0, true));
+        assert_eq!(visited.insert(&nodes[3]), (1, true));
+        assert_eq!(visited.insert(&nodes[7]), (0, false));
+        assert_eq!(visited.position(&nodes[3]), Some(1));
+        assert_eq!(visited.position(&nodes[5]), None);
+        assert_eq!(visited.position(&nodes[9]), None);
+        assert_eq!(visited.len(), 2);
+    }
+
+    #[test]
+    fn every_visited_set_should_number_states_in_order() {
+        should_number_states_in_order::<HashVisitedSet<GraphNode>>();
+        should_number_states_in_order::<FastVisitedSet<GraphNode>>();
+        should_number_states_in_order::<IndexedVisitedSet>();
+        #[cfg(feature = "parallel")]
+        should_number_states_in_order::<ShardedVisitedSet<GraphNode>>();
+    }
+
+    #[test]
+    fn bit_set_should_grow_and_iterate_in_order() {
+        let mut bits = BitSet::default();
+
+        for index in [150, 3, 64, 63, 0, 150] {
+            bits.insert(index);
+        }
+
+        assert!(!bits.insert(64));
+        assert!(bits.insert(65));
+        assert!(bits.contains(150));
+        assert!(!bits.contains(151));
+        assert!(!bits.contains(1000));
+        assert_eq!(bits.iter().collect::<Vec<_>>(), vec![0, 3, 63, 64, 65, 150]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn inserting_all_should_number_states_in_order() {
+        let nodes = GraphNode::undirected(100, &[]);
+        let mut visited = ShardedVisitedSet::default();
+        visited.insert(&nodes[50]);
+
+        let states: Vec<_> = [7, 50, 3, 7, 99, 3].iter().map(|&index| &nodes[index]).collect();
+        assert_eq!(visited.insert_all(&states), vec![Some(1), None, Some(2), None, Some(3), None]);
+
+        assert_eq!(visited.position(&nodes[99]), Some(3));
+        assert_eq!(visited.position(&nodes[50]), Some(0));
+        assert_eq!(visited.len(), 4);
+    }
+}